@@ -3,7 +3,14 @@ use std::fs::create_dir_all;
 
 use cosmwasm_schema::{export_schema, export_schema_with_title, remove_schemas, schema_for};
 
-use simple_option::msg::{ConfigResponse, HandleMsg, InitMsg, QueryMsg};
+use simple_option::msg::{
+    BreakevenResponse, CanResponse, CollateralValueResponse, ConfigResponse, ContractInfoResponse,
+    DenomsResponse, EffectiveCounterOfferResponse, ExercisePreviewResponse, ExpiryWarningResponse,
+    FlatResponse, HandleMsg, HealthCheckResponse, InitMsg, IsActiveResponse, LastHeightResponse,
+    LifecycleResponse, MigrateMsg, NftInfoResponse, OperationalStateResponse, OptionTypeResponse,
+    OwnerPnlResponse, PartiesResponse, PendingOwnerResponse, QueryMsg, RiskParamsResponse,
+    RoleResponse, SchemaVersionResponse, SimulateExecuteResponse, SolvencyResponse,
+};
 
 fn main() {
     let mut out_dir = current_dir().unwrap();
@@ -14,5 +21,30 @@ fn main() {
     export_schema(&schema_for!(InitMsg), &out_dir);
     export_schema(&schema_for!(HandleMsg), &out_dir);
     export_schema(&schema_for!(QueryMsg), &out_dir);
+    export_schema(&schema_for!(MigrateMsg), &out_dir);
     export_schema_with_title(&mut schema_for!(ConfigResponse), &out_dir, "ConfigResponse");
+    export_schema(&schema_for!(IsActiveResponse), &out_dir);
+    export_schema(&schema_for!(CollateralValueResponse), &out_dir);
+    export_schema(&schema_for!(LastHeightResponse), &out_dir);
+    export_schema(&schema_for!(CanResponse), &out_dir);
+    export_schema(&schema_for!(OptionTypeResponse), &out_dir);
+    export_schema(&schema_for!(SolvencyResponse), &out_dir);
+    export_schema(&schema_for!(LifecycleResponse), &out_dir);
+    export_schema(&schema_for!(BreakevenResponse), &out_dir);
+    export_schema(&schema_for!(PendingOwnerResponse), &out_dir);
+    export_schema(&schema_for!(DenomsResponse), &out_dir);
+    export_schema(&schema_for!(EffectiveCounterOfferResponse), &out_dir);
+    export_schema(&schema_for!(NftInfoResponse), &out_dir);
+    export_schema(&schema_for!(SimulateExecuteResponse), &out_dir);
+    export_schema(&schema_for!(ExpiryWarningResponse), &out_dir);
+    export_schema(&schema_for!(RiskParamsResponse), &out_dir);
+    export_schema(&schema_for!(SchemaVersionResponse), &out_dir);
+    export_schema(&schema_for!(RoleResponse), &out_dir);
+    export_schema(&schema_for!(FlatResponse), &out_dir);
+    export_schema(&schema_for!(ExercisePreviewResponse), &out_dir);
+    export_schema(&schema_for!(OwnerPnlResponse), &out_dir);
+    export_schema(&schema_for!(PartiesResponse), &out_dir);
+    export_schema(&schema_for!(HealthCheckResponse), &out_dir);
+    export_schema(&schema_for!(ContractInfoResponse), &out_dir);
+    export_schema(&schema_for!(OperationalStateResponse), &out_dir);
 }
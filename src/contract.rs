@@ -1,10 +1,88 @@
 use cosmwasm_std::{
-    to_binary, Api, BankMsg, Binary, Context, Env, Extern, HandleResponse, HumanAddr, InitResponse,
-    Querier, StdError, StdResult, Storage,
+    to_binary, to_vec, Api, BankMsg, Binary, Coin, Context, Decimal, Env, Extern, HandleResponse,
+    HumanAddr, InitResponse, MigrateResponse, Querier, StdError, StdResult, Storage, Uint128,
+    WasmMsg,
 };
 
-use crate::msg::{ConfigResponse, HandleMsg, InitMsg, QueryMsg};
-use crate::state::{config, config_read, State};
+use crate::msg::{
+    BreakevenResponse, CanResponse, CollateralValueResponse, ConfigResponse, ContractInfoResponse,
+    DenomsResponse, EffectiveCounterOfferResponse, ExercisePreviewResponse, ExpiryWarningResponse,
+    FlatResponse, HandleMsg, HealthCheckResponse, InitMsg, IsActiveResponse, LastHeightResponse,
+    LifecycleResponse, MigrateMsg, NftInfoResponse, OperationalStateResponse, OptionTypeResponse,
+    OwnerPnlResponse, PartiesResponse, PendingOwnerResponse, QueryMsg, ReasonCode,
+    RiskParamsResponse, Role, RoleResponse, SchemaVersionResponse, SimulateExecuteResponse,
+    SolvencyResponse, Trait,
+};
+use crate::oracle::query_price;
+use crate::registry::query_transferable;
+use crate::sanctions::query_sanctioned;
+use crate::state::{
+    admin, admin_read, config, config_read, config_v1_read, contract_addr, contract_addr_read,
+    last_height, last_height_read, state_version, state_version_read, CoinFormat, Metadata,
+    OptionType, State, MAX_DESCRIPTION_LEN, MAX_LABEL_LEN, MAX_NAME_LEN, STATE_VERSION,
+};
+
+/// Minimum number of blocks between creation and expiry, unless set in InitMsg
+pub const DEFAULT_MIN_LIFETIME: u64 = 1;
+/// Bounds the coins in collateral and counter_offer, and so the cost of settlement
+pub const MAX_DENOMS: usize = 8;
+/// Basis points making up the whole of an amount
+pub const BPS_TOTAL: u16 = 10_000;
+/// Bumped on every breaking change to a query response
+pub const SCHEMA_VERSION: u32 = 1;
+pub const CONTRACT_NAME: &str = env!("CARGO_PKG_NAME");
+pub const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+/// Column order of the Flat query, the id is the contract address
+pub const FLAT_COLUMNS: [&str; 8] = [
+    "id",
+    "creator",
+    "owner",
+    "status",
+    "expires",
+    "collateral_str",
+    "counter_offer_str",
+    "created_height",
+];
+/// Every HandleMsg, as named in the OperationalState query
+pub const HANDLERS: [&str; 25] = [
+    "transfer",
+    "execute",
+    "burn",
+    "force_expire",
+    "touch",
+    "set_metadata",
+    "set_beneficiary",
+    "claim_as_beneficiary",
+    "offer_transfer",
+    "accept_ownership",
+    "cancel_transfer",
+    "reissue",
+    "set_proceeds_recipient",
+    "donate",
+    "propose_shorten",
+    "accept_shorten",
+    "settle",
+    "set_referrer",
+    "liquidate",
+    "fund_collateral",
+    "set_auto_burn_bounty",
+    "freeze_option",
+    "unfreeze_option",
+    "set_burn_recipient",
+    "set_proceeds_callback",
+];
+/// The handlers FreezeOption blocks, every owner change and payout
+pub const FROZEN_HANDLERS: [&str; 9] = [
+    "transfer",
+    "execute",
+    "burn",
+    "claim_as_beneficiary",
+    "offer_transfer",
+    "accept_ownership",
+    "reissue",
+    "settle",
+    "liquidate",
+];
 
 pub fn init<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
@@ -14,20 +92,223 @@ pub fn init<S: Storage, A: Api, Q: Querier>(
     if msg.expires <= env.block.height {
         return Err(StdError::generic_err("Cannot create expired option"));
     }
+    let min_lifetime = msg.min_lifetime.unwrap_or(DEFAULT_MIN_LIFETIME);
+    if msg.expires < env.block.height.saturating_add(min_lifetime) {
+        return Err(StdError::generic_err(format!(
+            "Option must live at least {} blocks",
+            min_lifetime
+        )));
+    }
+
+    // store coins in canonical form, so comparisons don't depend on input order
+    let collateral = normalize_coins(env.message.sent_funds);
+    // a put inverts the legs: the creator locks the counter_offer, the owner pays the underlying
+    let counter_offer = match (&msg.option_type, msg.underlying) {
+        (OptionType::Call, None) => normalize_coins(msg.counter_offer),
+        (OptionType::Call, Some(_)) => {
+            return Err(StdError::generic_err("underlying is only used by a put"));
+        }
+        (OptionType::Put, None) => return Err(StdError::generic_err("a put must set underlying")),
+        (OptionType::Put, Some(underlying)) => {
+            if msg.counter_offer_ratio.is_some() || msg.allow_deferred_funding {
+                return Err(StdError::generic_err(
+                    "a put must lock its counter_offer at init",
+                ));
+            }
+            if collateral != normalize_coins(msg.counter_offer) {
+                return Err(StdError::generic_err(
+                    "a put must send exactly the counter_offer",
+                ));
+            }
+            normalize_coins(underlying)
+        }
+    };
+
+    if collateral.len() > MAX_DENOMS || counter_offer.len() > MAX_DENOMS {
+        return Err(StdError::generic_err(format!(
+            "Cannot use more than {} denoms",
+            MAX_DENOMS
+        )));
+    }
+
+    // without collateral the option waits for FundCollateral, if allowed
+    if collateral.is_empty() && !msg.allow_deferred_funding {
+        return Err(StdError::generic_err("must send collateral"));
+    }
+    let pending_funding = collateral.is_empty();
+
+    if msg.label.as_ref().map_or(0, String::len) > MAX_LABEL_LEN {
+        return Err(StdError::generic_err(format!(
+            "label longer than {} bytes",
+            MAX_LABEL_LEN
+        )));
+    }
+
+    let referral_bps = msg.referral_bps.unwrap_or(0);
+    if referral_bps >= BPS_TOTAL {
+        return Err(StdError::generic_err(format!(
+            "referral_bps must be below {}",
+            BPS_TOTAL
+        )));
+    }
+
+    if msg.require_transferable_check {
+        let registry = msg.denom_registry.as_ref().ok_or_else(|| {
+            StdError::generic_err("require_transferable_check needs a denom_registry")
+        })?;
+        for coin in counter_offer.iter() {
+            if !query_transferable(&deps.querier, registry, &coin.denom)? {
+                return Err(StdError::generic_err(format!(
+                    "counter_offer denom {} is not transferable",
+                    coin.denom
+                )));
+            }
+        }
+    }
+
+    if msg.maintenance_ratio_bps.is_some() && msg.oracle.is_none() {
+        return Err(StdError::generic_err(
+            "maintenance_ratio_bps requires an oracle",
+        ));
+    }
+
+    if msg.counter_offer_ratio.is_some() && msg.oracle.is_none() {
+        return Err(StdError::generic_err(
+            "counter_offer_ratio requires an oracle",
+        ));
+    }
+
+    if msg.settlement_denoms.is_some() && msg.oracle.is_none() {
+        return Err(StdError::generic_err(
+            "settlement_denoms requires an oracle",
+        ));
+    }
+
+    // an empty payment could not be sent to the creator on execute
+    if counter_offer.is_empty() && msg.counter_offer_ratio.is_none() {
+        return Err(StdError::generic_err(
+            "must set counter_offer or counter_offer_ratio",
+        ));
+    }
+
+    if let Some((_, _, den)) = &msg.counter_offer_ratio {
+        if !counter_offer.is_empty() {
+            return Err(StdError::generic_err(
+                "Cannot set both counter_offer and counter_offer_ratio",
+            ));
+        }
+        if *den == 0 {
+            return Err(StdError::generic_err(
+                "counter_offer_ratio denominator must be non-zero",
+            ));
+        }
+        if collateral.len() != 1 {
+            return Err(StdError::generic_err(
+                "counter_offer_ratio requires collateral in a single denom",
+            ));
+        }
+    }
 
     let state = State {
         creator: env.message.sender.clone(),
         owner: env.message.sender.clone(),
-        collateral: env.message.sent_funds,
-        counter_offer: msg.counter_offer,
+        admin: msg.admin,
+        oracle: msg.oracle,
+        collateral,
+        counter_offer,
+        counter_offer_ratio: msg.counter_offer_ratio,
         expires: msg.expires,
+        option_type: msg.option_type,
+        metadata: Metadata::default(),
+        beneficiary: None,
+        dust_threshold: msg.dust_threshold,
+        settlement_denoms: msg.settlement_denoms,
+        created_height: env.block.height,
+        min_lifetime,
+        pending_owner: None,
+        proceeds_recipient: None,
+        label: msg.label,
+        proposed_expires: None,
+        referrer: None,
+        referral_bps,
+        coin_attr_format: msg.coin_attr_format,
+        maintenance_ratio_bps: msg.maintenance_ratio_bps,
+        pending_funding,
+        burn_bounty: None,
+        sanctions: msg.sanctions,
+        frozen: false,
+        burn_recipient: None,
+        proceeds_callback: None,
     };
 
     config(&mut deps.storage).save(&state)?;
+    contract_addr(&mut deps.storage).save(&env.contract.address)?;
+    admin(&mut deps.storage).save(&state.admin)?;
+    state_version(&mut deps.storage).save(&STATE_VERSION)?;
 
     Ok(InitResponse::default())
 }
 
+/// Sorts coins by denom, merges duplicate denoms and drops zero amounts
+pub fn normalize_coins(mut coins: Vec<Coin>) -> Vec<Coin> {
+    coins.retain(|c| !c.amount.is_zero());
+    coins.sort_by(|a, b| a.denom.cmp(&b.denom));
+    coins.dedup_by(|next, kept| {
+        let same = next.denom == kept.denom;
+        if same {
+            kept.amount += next.amount;
+        }
+        same
+    });
+    coins
+}
+
+/// Formats coins for logs and attributes, as "1BTC,40ETH" or as a JSON array
+pub fn format_coins(coins: &[Coin], format: &CoinFormat) -> StdResult<String> {
+    match format {
+        CoinFormat::Compact => {
+            let parts: Vec<String> = coins
+                .iter()
+                .map(|c| format!("{}{}", c.amount, c.denom))
+                .collect();
+            Ok(parts.join(","))
+        }
+        CoinFormat::Json => {
+            String::from_utf8(to_vec(coins)?).map_err(|e| StdError::invalid_utf8(e.to_string()))
+        }
+    }
+}
+
+/// Upgrades the stored State to the current layout, filling new fields with defaults
+pub fn migrate<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    _msg: MigrateMsg,
+) -> StdResult<MigrateResponse> {
+    let version = state_version_read(&deps.storage).may_load()?.unwrap_or(1);
+    match version {
+        1 => {
+            // an executed or burned option has no state left to upgrade
+            if let Some(old) = config_v1_read(&deps.storage).may_load()? {
+                config(&mut deps.storage).save(&State::from(old))?;
+            }
+            contract_addr(&mut deps.storage).save(&env.contract.address)?;
+            // v1 had no admin
+            admin(&mut deps.storage).save(&None)?;
+        }
+        STATE_VERSION => {}
+        v => {
+            return Err(StdError::generic_err(format!(
+                "cannot migrate from unknown state version {}",
+                v
+            )))
+        }
+    }
+    state_version(&mut deps.storage).save(&STATE_VERSION)?;
+
+    Ok(MigrateResponse::default())
+}
+
 pub fn handle<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
     env: Env,
@@ -35,8 +316,53 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
 ) -> StdResult<HandleResponse> {
     match msg {
         HandleMsg::Transfer { recipient } => handle_transfer(deps, env, recipient),
-        HandleMsg::Execute {} => handle_execute(deps, env),
+        HandleMsg::Execute {
+            expected_counter_offer,
+            collateral_recipients,
+            max_price,
+            deadline,
+        } => handle_execute(
+            deps,
+            env,
+            expected_counter_offer,
+            collateral_recipients,
+            max_price,
+            deadline,
+        ),
         HandleMsg::Burn {} => handle_burn(deps, env),
+        HandleMsg::ForceExpire {} => handle_force_expire(deps, env),
+        HandleMsg::Touch {} => handle_touch(deps, env),
+        HandleMsg::SetMetadata { name, description } => {
+            handle_set_metadata(deps, env, name, description)
+        }
+        HandleMsg::SetBeneficiary { beneficiary } => handle_set_beneficiary(deps, env, beneficiary),
+        HandleMsg::ClaimAsBeneficiary {} => handle_claim_as_beneficiary(deps, env),
+        HandleMsg::OfferTransfer { recipient } => handle_offer_transfer(deps, env, recipient),
+        HandleMsg::AcceptOwnership {} => handle_accept_ownership(deps, env),
+        HandleMsg::CancelTransfer {} => handle_cancel_transfer(deps, env),
+        HandleMsg::Reissue {
+            new_expires,
+            new_counter_offer,
+        } => handle_reissue(deps, env, new_expires, new_counter_offer),
+        HandleMsg::SetProceedsRecipient { recipient } => {
+            handle_set_proceeds_recipient(deps, env, recipient)
+        }
+        HandleMsg::Donate {} => handle_donate(deps, env),
+        HandleMsg::ProposeShorten { new_expires } => handle_propose_shorten(deps, env, new_expires),
+        HandleMsg::AcceptShorten {} => handle_accept_shorten(deps, env),
+        HandleMsg::Settle {} => handle_settle(deps, env),
+        HandleMsg::SetReferrer { referrer } => handle_set_referrer(deps, env, referrer),
+        HandleMsg::Liquidate {} => handle_liquidate(deps, env),
+        HandleMsg::FundCollateral {} => handle_fund_collateral(deps, env),
+        HandleMsg::SetAutoBurnBounty { bounty } => handle_set_auto_burn_bounty(deps, env, bounty),
+        HandleMsg::FreezeOption {} => handle_set_frozen(deps, env, true),
+        HandleMsg::UnfreezeOption {} => handle_set_frozen(deps, env, false),
+        HandleMsg::SetBurnRecipient { recipient } => {
+            handle_set_burn_recipient(deps, env, recipient)
+        }
+        HandleMsg::SetProceedsCallback { callback } => {
+            handle_set_proceeds_callback(deps, env, callback)
+        }
     }
 }
 
@@ -45,14 +371,26 @@ pub fn handle_transfer<S: Storage, A: Api, Q: Querier>(
     env: Env,
     recipient: HumanAddr,
 ) -> StdResult<HandleResponse> {
-    // ensure msg sender is the owner
     let mut state = config(&mut deps.storage).load()?;
-    if env.message.sender != state.owner {
-        return Err(StdError::unauthorized());
+    check_transfer(
+        &deps.api,
+        &state,
+        &env.contract.address,
+        &env.message.sender,
+        &recipient,
+    )
+    .map_err(|(_, err)| err)?;
+
+    // ensure not sending any funds, they would be stuck in the contract
+    if !env.message.sent_funds.is_empty() {
+        return Err(StdError::generic_err("don't send funds with transfer"));
     }
 
-    // set new owner on state
+    // set new owner on state, the beneficiary and referrer were chosen by the previous owner
     state.owner = recipient.clone();
+    state.beneficiary = None;
+    state.referrer = None;
+    state.pending_owner = None;
     config(&mut deps.storage).save(&state)?;
 
     let mut res = Context::new();
@@ -61,282 +399,5383 @@ pub fn handle_transfer<S: Storage, A: Api, Q: Querier>(
     Ok(res.into())
 }
 
-pub fn handle_execute<S: Storage, A: Api, Q: Querier>(
+/// Checks the preconditions of handle_transfer and handle_offer_transfer,
+/// returning why either would be rejected
+fn check_transfer<A: Api>(
+    api: &A,
+    state: &State,
+    contract: &HumanAddr,
+    sender: &HumanAddr,
+    recipient: &HumanAddr,
+) -> Result<(), (ReasonCode, StdError)> {
+    // ensure msg sender is the owner
+    if sender != &state.owner {
+        return Err((ReasonCode::NotOwner, StdError::unauthorized()));
+    }
+
+    if state.frozen {
+        return Err((
+            ReasonCode::Frozen,
+            StdError::generic_err("option is frozen"),
+        ));
+    }
+
+    // reject self-transfer, it would only emit a misleading event
+    if recipient == &state.owner {
+        return Err((
+            ReasonCode::InvalidRecipient,
+            StdError::generic_err("cannot transfer to current owner"),
+        ));
+    }
+
+    // the contract can never act on the option, it would be stranded
+    if recipient == contract {
+        return Err((
+            ReasonCode::InvalidRecipient,
+            StdError::generic_err("cannot transfer to the contract itself"),
+        ));
+    }
+
+    api.canonical_address(recipient)
+        .map_err(|err| (ReasonCode::InvalidRecipient, err))?;
+
+    Ok(())
+}
+
+pub fn handle_offer_transfer<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
     env: Env,
+    recipient: HumanAddr,
 ) -> StdResult<HandleResponse> {
-    // ensure msg sender is the owner
-    let state = config(&mut deps.storage).load()?;
-    if env.message.sender != state.owner {
-        return Err(StdError::unauthorized());
+    let mut state = config(&mut deps.storage).load()?;
+    check_transfer(
+        &deps.api,
+        &state,
+        &env.contract.address,
+        &env.message.sender,
+        &recipient,
+    )
+    .map_err(|(_, err)| err)?;
+
+    // ensure not sending any funds, they would be stuck in the contract
+    if !env.message.sent_funds.is_empty() {
+        return Err(StdError::generic_err("don't send funds with transfer"));
     }
 
-    // ensure not expired
-    if env.block.height >= state.expires {
-        return Err(StdError::generic_err("option expired"));
+    // ownership only moves once the recipient accepts
+    state.pending_owner = Some(recipient.clone());
+    config(&mut deps.storage).save(&state)?;
+
+    let mut res = Context::new();
+    res.add_log("action", "offer_transfer");
+    res.add_log("pending_owner", recipient);
+    Ok(res.into())
+}
+
+pub fn handle_accept_ownership<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+) -> StdResult<HandleResponse> {
+    // ensure msg sender is the pending owner
+    let mut state = config(&mut deps.storage).load()?;
+    if state.pending_owner.as_ref() != Some(&env.message.sender) {
+        return Err(StdError::unauthorized());
     }
+    check_not_frozen(&state)?;
 
-    // ensure sending proper counter_offer
-    if env.message.sent_funds != state.counter_offer {
-        return Err(StdError::generic_err(format!(
-            "must send exact counter offer: {:?}",
-            state.counter_offer
-        )));
+    // ensure not sending any funds, they would be stuck in the contract
+    if !env.message.sent_funds.is_empty() {
+        return Err(StdError::generic_err("don't send funds with transfer"));
     }
 
-    // release counter_offer to creator
+    state.owner = env.message.sender.clone();
+    state.beneficiary = None;
+    state.pending_owner = None;
+    state.referrer = None;
+    config(&mut deps.storage).save(&state)?;
+
     let mut res = Context::new();
-    res.add_message(BankMsg::Send {
-        from_address: env.contract.address.clone(),
-        to_address: state.creator,
-        amount: state.counter_offer,
-    });
+    res.add_log("action", "accept_ownership");
+    res.add_log("owner", env.message.sender);
+    Ok(res.into())
+}
 
-    // release collateral to sender
-    res.add_message(BankMsg::Send {
-        from_address: env.contract.address,
-        to_address: state.owner,
-        amount: state.collateral,
-    });
+pub fn handle_cancel_transfer<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+) -> StdResult<HandleResponse> {
+    // ensure msg sender is the owner
+    let mut state = config(&mut deps.storage).load()?;
+    if env.message.sender != state.owner {
+        return Err(StdError::unauthorized());
+    }
 
-    // delete the option
-    config(&mut deps.storage).remove();
+    if state.pending_owner.is_none() {
+        return Err(StdError::generic_err("no pending transfer"));
+    }
+    state.pending_owner = None;
+    config(&mut deps.storage).save(&state)?;
 
-    res.add_log("action", "execute");
+    let mut res = Context::new();
+    res.add_log("action", "cancel_transfer");
     Ok(res.into())
 }
 
-pub fn handle_burn<S: Storage, A: Api, Q: Querier>(
+/// Any rejection, including execute after expiry, returns an error so the whole
+/// tx reverts: the sent_funds never leave the sender and nothing is refunded.
+pub fn handle_execute<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
     env: Env,
+    expected_counter_offer: Option<Vec<Coin>>,
+    collateral_recipients: Option<Vec<(HumanAddr, u16)>>,
+    max_price: Option<Uint128>,
+    deadline: Option<u64>,
 ) -> StdResult<HandleResponse> {
-    // ensure is expired
     let state = config(&mut deps.storage).load()?;
-    if env.block.height < state.expires {
-        return Err(StdError::generic_err("option not yet expired"));
+
+    // ensure the tx was included while the sender's quote still holds
+    if let Some(deadline) = deadline {
+        if env.block.height > deadline {
+            return Err(StdError::generic_err("execute deadline passed"));
+        }
+    }
+    if let Some(max_price) = max_price {
+        let required = required_amount(&deps.querier, &state, &env.message.sent_funds)?;
+        if required > max_price {
+            return Err(StdError::generic_err(format!(
+                "required payment {} exceeds max_price {}",
+                required, max_price
+            )));
+        }
     }
 
-    // ensure sending proper counter_offer
-    if !env.message.sent_funds.is_empty() {
-        return Err(StdError::generic_err("don't send funds with burn"));
+    // ensure the counter_offer did not change since the sender looked at it
+    if let Some(expected) = expected_counter_offer {
+        if normalize_coins(expected) != effective_counter_offer(&deps.querier, &state)? {
+            return Err(StdError::generic_err(
+                "counter offer differs from expected_counter_offer",
+            ));
+        }
     }
 
-    // release collateral to creator
-    let mut res = Context::new();
-    res.add_message(BankMsg::Send {
-        from_address: env.contract.address.clone(),
-        to_address: state.creator,
-        amount: state.collateral,
-    });
+    if let Some(recipients) = &collateral_recipients {
+        check_recipients(&deps.api, recipients)?;
+    }
+
+    let res = settle_execute(
+        &deps.querier,
+        &state,
+        &env.contract.address,
+        &env.message.sender,
+        env.message.sent_funds,
+        collateral_recipients.as_deref(),
+        env.block.height,
+    )?;
 
     // delete the option
     config(&mut deps.storage).remove();
 
-    res.add_log("action", "burn");
-    Ok(res.into())
+    Ok(res)
 }
 
-pub fn query<S: Storage, A: Api, Q: Querier>(
-    deps: &Extern<S, A, Q>,
-    msg: QueryMsg,
-) -> StdResult<Binary> {
-    match msg {
-        QueryMsg::Config {} => to_binary(&query_config(deps)?),
+/// Returns the amount of the one denom the owner pays in: the counter_offer's value
+/// in a settlement denom if funds are in one, else the counter_offer itself
+fn required_amount<Q: Querier>(querier: &Q, state: &State, funds: &[Coin]) -> StdResult<Uint128> {
+    let counter_offer = effective_counter_offer(querier, state)?;
+    if let ([payment], Some(denoms), Some(oracle)) =
+        (funds, &state.settlement_denoms, &state.oracle)
+    {
+        if denoms.contains(&payment.denom) && counter_offer.iter().all(|c| c.denom != payment.denom)
+        {
+            return value_in(querier, oracle, &counter_offer, &payment.denom);
+        }
+    }
+    match counter_offer.as_slice() {
+        [coin] => Ok(coin.amount),
+        _ => Err(StdError::generic_err(
+            "max_price needs a counter_offer in a single denom",
+        )),
     }
 }
 
-fn query_config<S: Storage, A: Api, Q: Querier>(
-    deps: &Extern<S, A, Q>,
-) -> StdResult<ConfigResponse> {
-    let state = config_read(&deps.storage).load()?;
-    Ok(state)
+/// Who receives what on an execute
+struct Settlement {
+    /// The payee's leg, then the referrer's cut if a referrer is set
+    payments: Vec<(HumanAddr, Vec<Coin>)>,
+    /// The released collateral, per collateral recipient
+    releases: Vec<(HumanAddr, Vec<Coin>)>,
+    /// Collateral below its dust_threshold, swept to the creator
+    dust: Vec<Coin>,
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use cosmwasm_std::testing::{mock_dependencies, mock_env, MOCK_CONTRACT_ADDR};
-    use cosmwasm_std::{coins, log, CosmosMsg};
-
-    #[test]
-    fn proper_initialization() {
-        let mut deps = mock_dependencies(20, &[]);
+/// Checks an execute and splits the payment and collateral between the parties,
+/// shared by settle_execute and the ExercisePreview query
+fn compute_settlement<Q: Querier>(
+    querier: &Q,
+    state: &State,
+    contract: &HumanAddr,
+    sender: &HumanAddr,
+    funds: &[Coin],
+    collateral_recipients: Option<&[(HumanAddr, u16)]>,
+    height: u64,
+) -> StdResult<Settlement> {
+    let funds = normalize_coins(funds.to_vec());
+    check_execute(querier, state, sender, &funds, height).map_err(|(_, err)| err)?;
 
-        let msg = InitMsg {
-            counter_offer: coins(40, "ETH"),
-            expires: 100_000,
-        };
-        let env = mock_env("creator", &coins(1, "BTC"));
+    // payment to creator (or their proceeds recipient),
+    // either the counter_offer or its value in a settlement denom,
+    // less the referral cut if the owner named a referrer
+    let payments = split_bps(&funds, &payees(state));
 
-        // we can just call .unwrap() to assert this was a success
-        let res = init(&mut deps, env, msg).unwrap();
-        assert_eq!(0, res.messages.len());
+    // collateral to sender, sweeping dust to the creator
+    let (release, dust) = split_dust(&state.collateral, state.dust_threshold.as_deref());
+    let balance = querier.query_all_balances(contract)?;
+    check_release(&state.collateral, &release, &dust, &balance)?;
+    let recipients = match collateral_recipients {
+        Some(recipients) => recipients.to_vec(),
+        None => vec![(state.owner.clone(), BPS_TOTAL)],
+    };
+    let releases = split_bps(&release, &recipients);
 
-        // it worked, let's query the state
-        let res = query_config(&deps).unwrap();
-        assert_eq!(100_000, res.expires);
-        assert_eq!("creator", res.owner.as_str());
-        assert_eq!("creator", res.creator.as_str());
-        assert_eq!(coins(1, "BTC"), res.collateral);
-        assert_eq!(coins(40, "ETH"), res.counter_offer);
+    // hold the option rather than pay anyone on the sanctions list
+    if let Some(sanctions) = &state.sanctions {
+        // the payee is always paid, everyone else only a non-zero amount
+        let paid = payments
+            .iter()
+            .enumerate()
+            .filter(|(i, (_, amount))| *i == 0 || !amount.is_empty())
+            .map(|(_, (recipient, _))| recipient)
+            .chain(
+                releases
+                    .iter()
+                    .filter(|(_, amount)| !amount.is_empty())
+                    .map(|(recipient, _)| recipient),
+            )
+            .chain(Some(&state.creator).filter(|_| !dust.is_empty()));
+        for to_address in paid {
+            if query_sanctioned(querier, sanctions, to_address)? {
+                return Err(StdError::generic_err(format!(
+                    "cannot settle to sanctioned address {}",
+                    to_address
+                )));
+            }
+        }
     }
 
-    #[test]
-    fn transfer() {
-        let mut deps = mock_dependencies(20, &[]);
-
-        let msg = InitMsg {
-            counter_offer: coins(40, "ETH"),
-            expires: 100_000,
-        };
-        let env = mock_env("creator", &coins(1, "BTC"));
+    Ok(Settlement {
+        payments,
+        releases,
+        dust,
+    })
+}
 
-        // we can just call .unwrap() to assert this was a success
-        let res = init(&mut deps, env, msg).unwrap();
-        assert_eq!(0, res.messages.len());
+/// Builds the messages and logs of an execute without touching storage,
+/// shared by handle_execute and the SimulateExecute query
+fn settle_execute<Q: Querier>(
+    querier: &Q,
+    state: &State,
+    contract: &HumanAddr,
+    sender: &HumanAddr,
+    funds: Vec<Coin>,
+    collateral_recipients: Option<&[(HumanAddr, u16)]>,
+    height: u64,
+) -> StdResult<HandleResponse> {
+    let settlement = compute_settlement(
+        querier,
+        state,
+        contract,
+        sender,
+        &funds,
+        collateral_recipients,
+        height,
+    )?;
 
-        // random cannot transfer
-        let env = mock_env("anyone", &[]);
-        let err = handle_transfer(&mut deps, env, HumanAddr::from("anyone")).unwrap_err();
-        match err {
-            StdError::Unauthorized { .. } => {}
-            e => panic!("unexpected error: {}", e),
+    let mut res = Context::new();
+    for (i, (recipient, amount)) in settlement.payments.into_iter().enumerate() {
+        // the payee is always sent its leg, the referrer only a non-zero cut
+        // the payee's leg goes with the creator's callback message, if they set one
+        match &state.proceeds_callback {
+            Some((_, msg)) if i == 0 => res.add_message(WasmMsg::Execute {
+                contract_addr: recipient,
+                msg: msg.clone(),
+                send: amount,
+            }),
+            _ if i == 0 || !amount.is_empty() => res.add_message(BankMsg::Send {
+                from_address: contract.clone(),
+                to_address: recipient,
+                amount,
+            }),
+            _ => {}
+        }
+    }
+    for (recipient, amount) in settlement.releases {
+        if !amount.is_empty() {
+            res.add_message(BankMsg::Send {
+                from_address: contract.clone(),
+                to_address: recipient,
+                amount,
+            });
         }
+    }
+    if !settlement.dust.is_empty() {
+        res.add_message(BankMsg::Send {
+            from_address: contract.clone(),
+            to_address: state.creator.clone(),
+            amount: settlement.dust,
+        });
+    }
 
-        // owner can transfer
-        let env = mock_env("creator", &[]);
-        let res = handle_transfer(&mut deps, env, HumanAddr::from("someone")).unwrap();
-        assert_eq!(res.log.len(), 2);
-        assert_eq!(res.log[0], log("action", "transfer"));
+    res.add_log("action", "execute");
+    res.add_log("option_type", state.option_type.as_str());
+    Ok(res.into())
+}
 
-        // check updated properly
-        let res = query_config(&deps).unwrap();
-        assert_eq!("someone", res.owner.as_str());
-        assert_eq!("creator", res.creator.as_str());
+/// Who shares the payment on execute: the creator (or their proceeds callback
+/// contract, or proceeds recipient), then the referrer if set
+fn payees(state: &State) -> Vec<(HumanAddr, u16)> {
+    let payee = match (&state.proceeds_callback, &state.proceeds_recipient) {
+        (Some((callback, _)), _) => callback.clone(),
+        (None, Some(recipient)) => recipient.clone(),
+        (None, None) => state.creator.clone(),
+    };
+    match &state.referrer {
+        Some(referrer) => vec![
+            (payee, BPS_TOTAL - state.referral_bps),
+            (referrer.clone(), state.referral_bps),
+        ],
+        None => vec![(payee, BPS_TOTAL)],
     }
+}
 
-    #[test]
-    fn execute() {
-        let mut deps = mock_dependencies(20, &[]);
+/// Ensures the recipients' shares add up to BPS_TOTAL and their addresses are valid
+fn check_recipients<A: Api>(api: &A, recipients: &[(HumanAddr, u16)]) -> StdResult<()> {
+    let total: u64 = recipients.iter().map(|(_, bps)| u64::from(*bps)).sum();
+    if total != u64::from(BPS_TOTAL) {
+        return Err(StdError::generic_err(format!(
+            "collateral_recipients must sum to {} bps",
+            BPS_TOTAL
+        )));
+    }
+    for (recipient, _) in recipients {
+        api.canonical_address(recipient)?;
+    }
+    Ok(())
+}
 
-        let counter_offer = coins(40, "ETH");
-        let collateral = coins(1, "BTC");
-        let msg = InitMsg {
-            counter_offer: counter_offer.clone(),
-            expires: 100_000,
-        };
-        let env = mock_env("creator", &collateral);
+/// Returns bps of amount rounded down, without overflowing for any u128
+fn bps_of(amount: u128, bps: u16) -> u128 {
+    let (bps, total) = (u128::from(bps), u128::from(BPS_TOTAL));
+    amount / total * bps + amount % total * bps / total
+}
 
-        // we can just call .unwrap() to assert this was a success
-        let _ = init(&mut deps, env, msg).unwrap();
+/// Splits each coin between recipients by bps, rounding down,
+/// with the remainder going to the first recipient
+fn split_bps(coins: &[Coin], recipients: &[(HumanAddr, u16)]) -> Vec<(HumanAddr, Vec<Coin>)> {
+    let mut shares: Vec<(HumanAddr, Vec<Coin>)> = recipients
+        .iter()
+        .map(|(r, _)| (r.clone(), vec![]))
+        .collect();
+    for coin in coins {
+        let mut rest = coin.amount.u128();
+        for (i, (_, bps)) in recipients.iter().enumerate().skip(1) {
+            let amount = bps_of(coin.amount.u128(), *bps);
+            rest -= amount;
+            shares[i].1.push(Coin {
+                denom: coin.denom.clone(),
+                amount: Uint128(amount),
+            });
+        }
+        shares[0].1.push(Coin {
+            denom: coin.denom.clone(),
+            amount: Uint128(rest),
+        });
+    }
+    for (_, amount) in shares.iter_mut() {
+        amount.retain(|c| !c.amount.is_zero());
+    }
+    shares
+}
 
-        // set new owner
-        let env = mock_env("creator", &[]);
+/// Splits coins into those at or above their denom's threshold, and the dust below it
+fn split_dust(coins: &[Coin], threshold: Option<&[Coin]>) -> (Vec<Coin>, Vec<Coin>) {
+    let threshold = threshold.unwrap_or_default();
+    coins.iter().cloned().partition(
+        |coin| match threshold.iter().find(|t| t.denom == coin.denom) {
+            Some(t) => coin.amount >= t.amount,
+            None => true,
+        },
+    )
+}
+
+/// Fails closed unless exactly the recorded collateral is released, and the contract holds it
+fn check_release(
+    collateral: &[Coin],
+    release: &[Coin],
+    dust: &[Coin],
+    balance: &[Coin],
+) -> StdResult<()> {
+    let released = normalize_coins([release, dust].concat());
+    if released.len() != collateral.len() || released != normalize_coins(collateral.to_vec()) {
+        return Err(StdError::generic_err(
+            "invalid release amount: does not match recorded collateral",
+        ));
+    }
+    check_balance(balance, &released)
+        .map_err(|_| StdError::generic_err("invalid release amount: exceeds contract balance"))
+}
+
+/// Checks the preconditions of handle_execute, returning why it would be rejected
+fn check_execute<Q: Querier>(
+    querier: &Q,
+    state: &State,
+    sender: &HumanAddr,
+    funds: &[Coin],
+    height: u64,
+) -> Result<(), (ReasonCode, StdError)> {
+    // ensure msg sender is the owner
+    if sender != &state.owner {
+        return Err((ReasonCode::NotOwner, StdError::unauthorized()));
+    }
+
+    if state.frozen {
+        return Err((
+            ReasonCode::Frozen,
+            StdError::generic_err("option is frozen"),
+        ));
+    }
+
+    // ensure not expired
+    if height >= state.expires {
+        return Err((ReasonCode::Expired, StdError::generic_err("option expired")));
+    }
+
+    // ensure there is collateral to exercise
+    if state.pending_funding {
+        return Err((
+            ReasonCode::NotFunded,
+            StdError::generic_err("option awaiting collateral"),
+        ));
+    }
+
+    // ensure sending proper counter_offer, however the funds are split or ordered
+    let funds = normalize_coins(funds.to_vec());
+    let funds = funds.as_slice();
+    let counter_offer =
+        effective_counter_offer(querier, state).map_err(|err| (ReasonCode::WrongFunds, err))?;
+
+    // a ratio on tiny collateral can round down to nothing, never exercise for free
+    if state.counter_offer_ratio.is_some() && counter_offer.iter().any(|c| c.amount.is_zero()) {
+        return Err((
+            ReasonCode::ZeroCounterOffer,
+            StdError::generic_err("counter offer rounds to zero"),
+        ));
+    }
+
+    if funds == counter_offer.as_slice() {
+        return Ok(());
+    }
+
+    // or at least its value in one of the settlement denoms
+    if let ([payment], Some(denoms), Some(oracle)) =
+        (funds, &state.settlement_denoms, &state.oracle)
+    {
+        if denoms.contains(&payment.denom) {
+            let required = value_in(querier, oracle, &counter_offer, &payment.denom)
+                .map_err(|err| (ReasonCode::WrongFunds, err))?;
+            if payment.amount >= required {
+                return Ok(());
+            }
+            return Err((
+                ReasonCode::WrongFunds,
+                StdError::generic_err(format!(
+                    "must send at least {}{} for the counter offer",
+                    required, payment.denom
+                )),
+            ));
+        }
+    }
+
+    Err((
+        ReasonCode::WrongFunds,
+        StdError::generic_err(format!(
+            "must send exact counter offer: {:?}",
+            counter_offer
+        )),
+    ))
+}
+
+/// Sums the value of coins in quote_denom, as priced by the oracle
+fn value_in<Q: Querier>(
+    querier: &Q,
+    oracle: &HumanAddr,
+    coins: &[Coin],
+    quote_denom: &str,
+) -> StdResult<Uint128> {
+    let mut value = Uint128::zero();
+    for coin in coins {
+        let price = query_price(querier, oracle, &coin.denom, quote_denom)?;
+        value += coin.amount * price;
+    }
+    Ok(value)
+}
+
+/// Returns the collateral's value as bps of the counter_offer's, both priced by the oracle
+/// in the first counter_offer denom
+fn collateral_ratio_bps<Q: Querier>(querier: &Q, state: &State) -> StdResult<u64> {
+    let oracle = state
+        .oracle
+        .as_ref()
+        .ok_or_else(|| StdError::generic_err("no oracle configured"))?;
+    let counter_offer = effective_counter_offer(querier, state)?;
+    let quote_denom = counter_offer
+        .first()
+        .map(|c| c.denom.clone())
+        .ok_or_else(|| StdError::generic_err("no counter_offer to value against"))?;
+
+    let counter_value = value_in(querier, oracle, &counter_offer, &quote_denom)?;
+    if counter_value.is_zero() {
+        return Err(StdError::generic_err("no counter_offer to value against"));
+    }
+    let collateral_value = value_in(querier, oracle, &state.collateral, &quote_denom)?;
+    let ratio = collateral_value.multiply_ratio(BPS_TOTAL, counter_value);
+    Ok(ratio.u128().min(u128::from(u64::MAX)) as u64)
+}
+
+/// Returns the counter_offer required to execute, resolving a ratio against the
+/// collateral's value in the ratio's denom, as priced by the oracle
+pub fn effective_counter_offer<Q: Querier>(querier: &Q, state: &State) -> StdResult<Vec<Coin>> {
+    match &state.counter_offer_ratio {
+        Some((denom, num, den)) => {
+            let oracle = state
+                .oracle
+                .as_ref()
+                .ok_or_else(|| StdError::generic_err("no oracle configured"))?;
+            let value = value_in(querier, oracle, &state.collateral, denom)?;
+            Ok(vec![Coin {
+                denom: denom.clone(),
+                amount: value.multiply_ratio(*num, *den),
+            }])
+        }
+        None => Ok(state.counter_offer.clone()),
+    }
+}
+
+pub fn handle_burn<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+) -> StdResult<HandleResponse> {
+    let state = config(&mut deps.storage).load()?;
+    check_burn(&state, &env.message.sent_funds, env.block.height).map_err(|(_, err)| err)?;
+
+    // ensure the contract actually holds the collateral, rather than emit a failing send
+    let balance = deps.querier.query_all_balances(&env.contract.address)?;
+    check_balance(&balance, &state.collateral)?;
+
+    // release collateral to creator (or their burn recipient), less any bounty for the burner
+    let bounty = state.burn_bounty.unwrap_or_default();
+    let remainder = shortfall(&bounty, &state.collateral);
+    let mut res = Context::new();
+    if !remainder.is_empty() {
+        res.add_message(BankMsg::Send {
+            from_address: env.contract.address.clone(),
+            to_address: state.burn_recipient.unwrap_or(state.creator),
+            amount: remainder,
+        });
+    }
+    if !bounty.is_empty() {
+        res.add_message(BankMsg::Send {
+            from_address: env.contract.address.clone(),
+            to_address: env.message.sender,
+            amount: bounty,
+        });
+    }
+
+    // delete the option
+    config(&mut deps.storage).remove();
+
+    res.add_log("action", "burn");
+    Ok(res.into())
+}
+
+/// Burns an expired option, otherwise executes it, with the same rules as calling either
+pub fn handle_settle<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+) -> StdResult<HandleResponse> {
+    let state = config(&mut deps.storage).load()?;
+    if env.block.height >= state.expires {
+        handle_burn(deps, env)
+    } else {
+        handle_execute(deps, env, None, None, None, None)
+    }
+}
+
+/// Lets the creator close the option early once the collateral is worth less than
+/// maintenance_ratio_bps of the counter_offer
+pub fn handle_liquidate<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+) -> StdResult<HandleResponse> {
+    // ensure msg sender is the creator
+    let state = config(&mut deps.storage).load()?;
+    if env.message.sender != state.creator {
+        return Err(StdError::unauthorized());
+    }
+    check_not_frozen(&state)?;
+
+    // an unfunded option has nothing to return, an expired one is closed by burn
+    if state.pending_funding {
+        return Err(StdError::generic_err("option awaiting collateral"));
+    }
+    if env.block.height >= state.expires {
+        return Err(StdError::generic_err("option expired, burn it instead"));
+    }
+
+    let maintenance = state
+        .maintenance_ratio_bps
+        .ok_or_else(|| StdError::generic_err("no maintenance ratio configured"))?;
+    let ratio = collateral_ratio_bps(&deps.querier, &state)?;
+    if ratio >= maintenance {
+        return Err(StdError::generic_err(format!(
+            "collateral at {} bps is not below maintenance ratio {} bps",
+            ratio, maintenance
+        )));
+    }
+
+    let balance = deps.querier.query_all_balances(&env.contract.address)?;
+    check_balance(&balance, &state.collateral)?;
+
+    // return collateral to creator
+    let mut res = Context::new();
+    res.add_message(BankMsg::Send {
+        from_address: env.contract.address.clone(),
+        to_address: state.creator,
+        amount: state.collateral,
+    });
+
+    // delete the option
+    config(&mut deps.storage).remove();
+
+    res.add_log("action", "liquidate");
+    res.add_log("ratio_bps", ratio);
+    Ok(res.into())
+}
+
+/// Ensures the admin has not frozen the option
+fn check_not_frozen(state: &State) -> StdResult<()> {
+    if state.frozen {
+        return Err(StdError::generic_err("option is frozen"));
+    }
+    Ok(())
+}
+
+/// Ensures balance covers every coin in required
+fn check_balance(balance: &[Coin], required: &[Coin]) -> StdResult<()> {
+    for coin in required {
+        let have = balance
+            .iter()
+            .find(|c| c.denom == coin.denom)
+            .map_or(Uint128::zero(), |c| c.amount);
+        if have < coin.amount {
+            return Err(StdError::generic_err(format!(
+                "insufficient contract balance: need {}{}, have {}{}",
+                coin.amount, coin.denom, have, coin.denom
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Returns how much of each coin in required is missing from balance
+fn shortfall(balance: &[Coin], required: &[Coin]) -> Vec<Coin> {
+    required
+        .iter()
+        .filter_map(|coin| {
+            let have = balance
+                .iter()
+                .find(|c| c.denom == coin.denom)
+                .map_or(Uint128::zero(), |c| c.amount);
+            (coin.amount - have)
+                .ok()
+                .filter(|short| !short.is_zero())
+                .map(|amount| Coin {
+                    denom: coin.denom.clone(),
+                    amount,
+                })
+        })
+        .collect()
+}
+
+/// Checks the preconditions of handle_burn, returning why it would be rejected
+fn check_burn(state: &State, funds: &[Coin], height: u64) -> Result<(), (ReasonCode, StdError)> {
+    if state.frozen {
+        return Err((
+            ReasonCode::Frozen,
+            StdError::generic_err("option is frozen"),
+        ));
+    }
+
+    // ensure is expired
+    if height < state.expires {
+        return Err((
+            ReasonCode::NotExpired,
+            StdError::generic_err("option not yet expired"),
+        ));
+    }
+
+    // ensure not sending any funds
+    if !funds.is_empty() {
+        return Err((
+            ReasonCode::UnexpectedFunds,
+            StdError::generic_err("don't send funds with burn"),
+        ));
+    }
+
+    Ok(())
+}
+
+pub fn handle_force_expire<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+) -> StdResult<HandleResponse> {
+    // ensure msg sender is the admin
+    let mut state = config(&mut deps.storage).load()?;
+    if state.admin.as_ref() != Some(&env.message.sender) {
+        return Err(StdError::unauthorized());
+    }
+
+    // expire now, so execute is rejected and burn is allowed,
+    // and drop any proposal so accepting it cannot push expiry back out
+    state.expires = env.block.height;
+    state.proposed_expires = None;
+    config(&mut deps.storage).save(&state)?;
+
+    let mut res = Context::new();
+    res.add_log("action", "force_expire");
+    res.add_log("expires", state.expires);
+    Ok(res.into())
+}
+
+pub fn handle_set_frozen<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    frozen: bool,
+) -> StdResult<HandleResponse> {
+    // ensure msg sender is the admin
+    let mut state = config(&mut deps.storage).load()?;
+    if state.admin.as_ref() != Some(&env.message.sender) {
+        return Err(StdError::unauthorized());
+    }
+
+    state.frozen = frozen;
+    config(&mut deps.storage).save(&state)?;
+
+    let mut res = Context::new();
+    res.add_log("action", if frozen { "freeze" } else { "unfreeze" });
+    Ok(res.into())
+}
+
+pub fn handle_touch<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+) -> StdResult<HandleResponse> {
+    last_height(&mut deps.storage).save(&env.block.height)?;
+
+    let mut res = Context::new();
+    res.add_log("action", "touch");
+    res.add_log("height", env.block.height);
+    Ok(res.into())
+}
+
+pub fn handle_set_metadata<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    name: Option<String>,
+    description: Option<String>,
+) -> StdResult<HandleResponse> {
+    // ensure msg sender is the creator
+    let mut state = config(&mut deps.storage).load()?;
+    if env.message.sender != state.creator {
+        return Err(StdError::unauthorized());
+    }
+
+    // ensure metadata is bounded
+    if name.as_ref().map_or(0, String::len) > MAX_NAME_LEN {
+        return Err(StdError::generic_err(format!(
+            "name longer than {} bytes",
+            MAX_NAME_LEN
+        )));
+    }
+    if description.as_ref().map_or(0, String::len) > MAX_DESCRIPTION_LEN {
+        return Err(StdError::generic_err(format!(
+            "description longer than {} bytes",
+            MAX_DESCRIPTION_LEN
+        )));
+    }
+
+    state.metadata = Metadata { name, description };
+    config(&mut deps.storage).save(&state)?;
+
+    let mut res = Context::new();
+    res.add_log("action", "set_metadata");
+    Ok(res.into())
+}
+
+pub fn handle_set_beneficiary<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    beneficiary: Option<HumanAddr>,
+) -> StdResult<HandleResponse> {
+    // ensure msg sender is the owner
+    let mut state = config(&mut deps.storage).load()?;
+    if env.message.sender != state.owner {
+        return Err(StdError::unauthorized());
+    }
+
+    // ensure the beneficiary is an address that could claim
+    if let Some(beneficiary) = &beneficiary {
+        deps.api.canonical_address(beneficiary)?;
+    }
+
+    state.beneficiary = beneficiary;
+    config(&mut deps.storage).save(&state)?;
+
+    let mut res = Context::new();
+    res.add_log("action", "set_beneficiary");
+    if let Some(beneficiary) = state.beneficiary {
+        res.add_log("beneficiary", beneficiary);
+    }
+    Ok(res.into())
+}
+
+pub fn handle_claim_as_beneficiary<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+) -> StdResult<HandleResponse> {
+    // ensure msg sender is the beneficiary
+    let mut state = config(&mut deps.storage).load()?;
+    if state.beneficiary.as_ref() != Some(&env.message.sender) {
+        return Err(StdError::unauthorized());
+    }
+    check_not_frozen(&state)?;
+
+    // ensure is expired
+    if env.block.height < state.expires {
+        return Err(StdError::generic_err("option not yet expired"));
+    }
+
+    // ownership only, the collateral stays until burned,
+    // and any offer made by the previous owner lapses
+    state.owner = env.message.sender.clone();
+    state.beneficiary = None;
+    state.pending_owner = None;
+    state.referrer = None;
+    config(&mut deps.storage).save(&state)?;
+
+    let mut res = Context::new();
+    res.add_log("action", "claim_as_beneficiary");
+    res.add_log("owner", env.message.sender);
+    Ok(res.into())
+}
+
+pub fn handle_reissue<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    new_expires: u64,
+    new_counter_offer: Vec<Coin>,
+) -> StdResult<HandleResponse> {
+    // ensure msg sender is the creator
+    let mut state = config(&mut deps.storage).load()?;
+    if env.message.sender != state.creator {
+        return Err(StdError::unauthorized());
+    }
+
+    check_not_frozen(&state)?;
+
+    // ensure no one else still holds a live option
+    if state.owner != state.creator && env.block.height < state.expires {
+        return Err(StdError::generic_err("option not yet expired"));
+    }
+
+    // same lifetime rules as a new option, holding the creator to the init min_lifetime
+    if new_expires <= env.block.height {
+        return Err(StdError::generic_err("Cannot create expired option"));
+    }
+    if new_expires < env.block.height.saturating_add(state.min_lifetime) {
+        return Err(StdError::generic_err(format!(
+            "Option must live at least {} blocks",
+            state.min_lifetime
+        )));
+    }
+    let new_counter_offer = normalize_coins(new_counter_offer);
+    if new_counter_offer.is_empty() {
+        return Err(StdError::generic_err("must set counter_offer"));
+    }
+    if new_counter_offer.len() > MAX_DENOMS {
+        return Err(StdError::generic_err(format!(
+            "Cannot use more than {} denoms",
+            MAX_DENOMS
+        )));
+    }
+
+    // write the fresh option over the same collateral
+    state.owner = state.creator.clone();
+    state.created_height = env.block.height;
+    state.expires = new_expires;
+    state.counter_offer = new_counter_offer;
+    state.counter_offer_ratio = None;
+    state.beneficiary = None;
+    state.pending_owner = None;
+    state.referrer = None;
+    state.proposed_expires = None;
+    config(&mut deps.storage).save(&state)?;
+
+    let mut res = Context::new();
+    res.add_log("action", "reissue");
+    res.add_log("expires", new_expires);
+    Ok(res.into())
+}
+
+pub fn handle_set_proceeds_recipient<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    recipient: HumanAddr,
+) -> StdResult<HandleResponse> {
+    // ensure msg sender is the creator
+    let mut state = config(&mut deps.storage).load()?;
+    if env.message.sender != state.creator {
+        return Err(StdError::unauthorized());
+    }
+
+    // ensure the proceeds can actually be sent there
+    deps.api.canonical_address(&recipient)?;
+
+    state.proceeds_recipient = Some(recipient.clone());
+    config(&mut deps.storage).save(&state)?;
+
+    let mut res = Context::new();
+    res.add_log("action", "set_proceeds_recipient");
+    res.add_log("recipient", recipient);
+    Ok(res.into())
+}
+
+pub fn handle_set_burn_recipient<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    recipient: Option<HumanAddr>,
+) -> StdResult<HandleResponse> {
+    // ensure msg sender is the creator
+    let mut state = config(&mut deps.storage).load()?;
+    if env.message.sender != state.creator {
+        return Err(StdError::unauthorized());
+    }
+
+    // ensure the collateral can actually be sent there
+    if let Some(recipient) = &recipient {
+        deps.api.canonical_address(recipient)?;
+    }
+
+    state.burn_recipient = recipient;
+    config(&mut deps.storage).save(&state)?;
+
+    let mut res = Context::new();
+    res.add_log("action", "set_burn_recipient");
+    if let Some(recipient) = &state.burn_recipient {
+        res.add_log("recipient", recipient);
+    }
+    Ok(res.into())
+}
+
+pub fn handle_set_proceeds_callback<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    callback: Option<(HumanAddr, Binary)>,
+) -> StdResult<HandleResponse> {
+    // ensure msg sender is the creator
+    let mut state = config(&mut deps.storage).load()?;
+    if env.message.sender != state.creator {
+        return Err(StdError::unauthorized());
+    }
+
+    // ensure the proceeds can actually be sent there
+    if let Some((contract, _)) = &callback {
+        deps.api.canonical_address(contract)?;
+    }
+
+    state.proceeds_callback = callback;
+    config(&mut deps.storage).save(&state)?;
+
+    let mut res = Context::new();
+    res.add_log("action", "set_proceeds_callback");
+    if let Some((contract, _)) = &state.proceeds_callback {
+        res.add_log("contract", contract);
+    }
+    Ok(res.into())
+}
+
+pub fn handle_donate<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+) -> StdResult<HandleResponse> {
+    let donation = normalize_coins(env.message.sent_funds);
+    if donation.is_empty() {
+        return Err(StdError::generic_err("must send funds to donate"));
+    }
+
+    // a donation would change a ratio counter_offer under the owner's feet
+    let mut state = config(&mut deps.storage).load()?;
+    if state.counter_offer_ratio.is_some() {
+        return Err(StdError::generic_err(
+            "cannot donate to a counter_offer_ratio option",
+        ));
+    }
+    // the creator funds it first, junk denoms could otherwise fill MAX_DENOMS
+    if state.pending_funding {
+        return Err(StdError::generic_err("option awaiting collateral"));
+    }
+
+    // anyone can add to the collateral, it all goes to whoever settles the option
+    state.collateral = normalize_coins([state.collateral, donation.clone()].concat());
+    if state.collateral.len() > MAX_DENOMS {
+        return Err(StdError::generic_err(format!(
+            "Cannot use more than {} denoms",
+            MAX_DENOMS
+        )));
+    }
+    config(&mut deps.storage).save(&state)?;
+
+    let mut res = Context::new();
+    res.add_log("action", "donate");
+    res.add_log("donor", env.message.sender);
+    res.add_log("amount", format_coins(&donation, &state.coin_attr_format)?);
+    Ok(res.into())
+}
+
+pub fn handle_propose_shorten<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    new_expires: u64,
+) -> StdResult<HandleResponse> {
+    // ensure msg sender is the creator
+    let mut state = config(&mut deps.storage).load()?;
+    if env.message.sender != state.creator {
+        return Err(StdError::unauthorized());
+    }
+
+    if env.block.height >= state.expires {
+        return Err(StdError::generic_err("option expired"));
+    }
+    if new_expires >= state.expires {
+        return Err(StdError::generic_err("new_expires must be before expires"));
+    }
+    if new_expires <= env.block.height {
+        return Err(StdError::generic_err("Cannot create expired option"));
+    }
+
+    // only takes effect once the owner accepts
+    state.proposed_expires = Some(new_expires);
+    config(&mut deps.storage).save(&state)?;
+
+    let mut res = Context::new();
+    res.add_log("action", "propose_shorten");
+    res.add_log("expires", new_expires);
+    Ok(res.into())
+}
+
+pub fn handle_accept_shorten<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+) -> StdResult<HandleResponse> {
+    // ensure msg sender is the owner
+    let mut state = config(&mut deps.storage).load()?;
+    if env.message.sender != state.owner {
+        return Err(StdError::unauthorized());
+    }
+
+    let new_expires = state
+        .proposed_expires
+        .ok_or_else(|| StdError::generic_err("no proposed expiry"))?;
+    if env.block.height >= new_expires {
+        return Err(StdError::generic_err("option expired"));
+    }
+    // expiry may have moved since the proposal, this can only ever shorten it
+    if new_expires >= state.expires {
+        return Err(StdError::generic_err("new_expires must be before expires"));
+    }
+
+    state.expires = new_expires;
+    state.proposed_expires = None;
+    config(&mut deps.storage).save(&state)?;
+
+    let mut res = Context::new();
+    res.add_log("action", "accept_shorten");
+    res.add_log("expires", new_expires);
+    Ok(res.into())
+}
+
+pub fn handle_set_referrer<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    referrer: HumanAddr,
+) -> StdResult<HandleResponse> {
+    // ensure msg sender is the owner
+    let mut state = config(&mut deps.storage).load()?;
+    if env.message.sender != state.owner {
+        return Err(StdError::unauthorized());
+    }
+
+    // can only be set once, so the cut cannot be redirected later
+    if state.referrer.is_some() {
+        return Err(StdError::generic_err("referrer already set"));
+    }
+    deps.api.canonical_address(&referrer)?;
+
+    // a party's own referral would just be a discount on the counter_offer
+    if referrer == state.owner || referrer == state.creator {
+        return Err(StdError::generic_err(
+            "referrer cannot be the owner or creator",
+        ));
+    }
+
+    state.referrer = Some(referrer.clone());
+    config(&mut deps.storage).save(&state)?;
+
+    let mut res = Context::new();
+    res.add_log("action", "set_referrer");
+    res.add_log("referrer", referrer);
+    Ok(res.into())
+}
+
+pub fn handle_fund_collateral<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+) -> StdResult<HandleResponse> {
+    // ensure msg sender is the creator
+    let mut state = config(&mut deps.storage).load()?;
+    if env.message.sender != state.creator {
+        return Err(StdError::unauthorized());
+    }
+
+    if !state.pending_funding {
+        return Err(StdError::generic_err("option already funded"));
+    }
+    if env.block.height >= state.expires {
+        return Err(StdError::generic_err("option expired"));
+    }
+    let collateral = normalize_coins(env.message.sent_funds);
+    if collateral.is_empty() {
+        return Err(StdError::generic_err("must send collateral"));
+    }
+
+    // nothing can be donated until it is funded, so this is all of the collateral
+    state.collateral = collateral.clone();
+    if state.collateral.len() > MAX_DENOMS {
+        return Err(StdError::generic_err(format!(
+            "Cannot use more than {} denoms",
+            MAX_DENOMS
+        )));
+    }
+    state.pending_funding = false;
+    config(&mut deps.storage).save(&state)?;
+
+    let mut res = Context::new();
+    res.add_log("action", "fund_collateral");
+    res.add_log(
+        "amount",
+        format_coins(&collateral, &state.coin_attr_format)?,
+    );
+    Ok(res.into())
+}
+
+pub fn handle_set_auto_burn_bounty<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    bounty: Option<Vec<Coin>>,
+) -> StdResult<HandleResponse> {
+    // ensure msg sender is the creator
+    let mut state = config(&mut deps.storage).load()?;
+    if env.message.sender != state.creator {
+        return Err(StdError::unauthorized());
+    }
+
+    // paid out of the collateral the creator would get back on burn
+    let bounty = bounty.map(normalize_coins).filter(|b| !b.is_empty());
+    if let Some(bounty) = &bounty {
+        if !shortfall(&state.collateral, bounty).is_empty() {
+            return Err(StdError::generic_err("burn bounty exceeds collateral"));
+        }
+    }
+
+    state.burn_bounty = bounty;
+    config(&mut deps.storage).save(&state)?;
+
+    let mut res = Context::new();
+    res.add_log("action", "set_auto_burn_bounty");
+    Ok(res.into())
+}
+
+pub fn query<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    msg: QueryMsg,
+) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Config {} => to_binary(&query_config(deps)?),
+        QueryMsg::IsActive {} => to_binary(&query_is_active(deps)?),
+        QueryMsg::CollateralValue { quote_denom } => {
+            to_binary(&query_collateral_value(deps, quote_denom)?)
+        }
+        QueryMsg::LastHeight {} => to_binary(&query_last_height(deps)?),
+        QueryMsg::CanExecute { sender, funds } => {
+            to_binary(&query_can_execute(deps, sender, funds)?)
+        }
+        QueryMsg::CanBurn { funds } => to_binary(&query_can_burn(deps, funds)?),
+        QueryMsg::OptionType {} => to_binary(&query_option_type(deps)?),
+        QueryMsg::Solvency {} => to_binary(&query_solvency(deps)?),
+        QueryMsg::Lifecycle {} => to_binary(&query_lifecycle(deps)?),
+        QueryMsg::Breakeven { quote_denom } => to_binary(&query_breakeven(deps, quote_denom)?),
+        QueryMsg::PendingOwner {} => to_binary(&query_pending_owner(deps)?),
+        QueryMsg::Denoms {} => to_binary(&query_denoms(deps)?),
+        QueryMsg::EffectiveCounterOffer {} => to_binary(&query_effective_counter_offer(deps)?),
+        QueryMsg::NftInfo {} => to_binary(&query_nft_info(deps)?),
+        QueryMsg::SimulateExecute {
+            sender,
+            funds,
+            collateral_recipients,
+        } => to_binary(&query_simulate_execute(
+            deps,
+            sender,
+            funds,
+            collateral_recipients,
+        )?),
+        QueryMsg::ExpiryWarning { threshold_blocks } => {
+            to_binary(&query_expiry_warning(deps, threshold_blocks)?)
+        }
+        QueryMsg::RiskParams {} => to_binary(&query_risk_params(deps)?),
+        QueryMsg::SchemaVersion {} => to_binary(&query_schema_version()),
+        QueryMsg::Role { address } => to_binary(&query_role(deps, address)?),
+        QueryMsg::CanTransfer { sender, recipient } => {
+            to_binary(&query_can_transfer(deps, sender, recipient)?)
+        }
+        QueryMsg::Flat {} => to_binary(&query_flat(deps)?),
+        QueryMsg::ExercisePreview {} => to_binary(&query_exercise_preview(deps)?),
+        QueryMsg::OwnerPnl {
+            spot_price,
+            quote_denom,
+        } => to_binary(&query_owner_pnl(deps, spot_price, quote_denom)?),
+        QueryMsg::Parties {} => to_binary(&query_parties(deps)?),
+        QueryMsg::HealthCheck {} => to_binary(&query_health_check(deps)?),
+        QueryMsg::ContractInfo {} => to_binary(&query_contract_info(deps)?),
+        QueryMsg::OperationalState {} => to_binary(&query_operational_state(deps)?),
+    }
+}
+
+fn query_config<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+) -> StdResult<ConfigResponse> {
+    let state = config_read(&deps.storage).load()?;
+    Ok(state)
+}
+
+fn query_is_active<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+) -> StdResult<IsActiveResponse> {
+    // execute and burn remove the config, so existence means active
+    // unless frozen or still waiting for its collateral
+    let state = config_read(&deps.storage).may_load()?;
+    Ok(IsActiveResponse {
+        active: matches!(state, Some(state) if !state.frozen && !state.pending_funding),
+    })
+}
+
+fn query_collateral_value<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    quote_denom: String,
+) -> StdResult<CollateralValueResponse> {
+    let state = config_read(&deps.storage).load()?;
+    let oracle = state
+        .oracle
+        .ok_or_else(|| StdError::generic_err("no oracle configured"))?;
+
+    let value = value_in(&deps.querier, &oracle, &state.collateral, &quote_denom)?;
+    Ok(CollateralValueResponse { quote_denom, value })
+}
+
+fn query_last_height<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+) -> StdResult<LastHeightResponse> {
+    let height = last_height_read(&deps.storage).may_load()?;
+    Ok(LastHeightResponse { height })
+}
+
+fn query_option_type<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+) -> StdResult<OptionTypeResponse> {
+    let state = config_read(&deps.storage).load()?;
+    Ok(OptionTypeResponse {
+        option_type: state.option_type,
+    })
+}
+
+fn query_solvency<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+) -> StdResult<SolvencyResponse> {
+    // nothing is owed once the option is executed or burned
+    let obligations = match config_read(&deps.storage).may_load()? {
+        Some(state) => state.collateral,
+        None => vec![],
+    };
+    let address = contract_addr_read(&deps.storage).load()?;
+    let balance = deps.querier.query_all_balances(address)?;
+    let shortfall = shortfall(&balance, &obligations);
+    Ok(SolvencyResponse {
+        solvent: shortfall.is_empty(),
+        shortfall,
+    })
+}
+
+fn query_lifecycle<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+) -> StdResult<LifecycleResponse> {
+    let state = config_read(&deps.storage).load()?;
+    let now = last_height_read(&deps.storage).may_load()?;
+    Ok(LifecycleResponse {
+        created_height: state.created_height,
+        exercise_from: None,
+        expires: state.expires,
+        grace_until: None,
+        now,
+    })
+}
+
+fn query_breakeven<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    quote_denom: String,
+) -> StdResult<BreakevenResponse> {
+    let state = config_read(&deps.storage).load()?;
+    let oracle = state
+        .oracle
+        .as_ref()
+        .ok_or_else(|| StdError::generic_err("no oracle configured"))?;
+
+    // a basket has no single per-unit price
+    let collateral = match state.collateral.as_slice() {
+        [coin] if !coin.amount.is_zero() => coin,
+        _ => {
+            return Err(StdError::generic_err(
+                "breakeven requires collateral in a single denom",
+            ))
+        }
+    };
+
+    let cost = value_in(
+        &deps.querier,
+        oracle,
+        &effective_counter_offer(&deps.querier, &state)?,
+        &quote_denom,
+    )?;
+    Ok(BreakevenResponse {
+        price: Decimal::from_ratio(cost, collateral.amount),
+        quote_denom,
+    })
+}
+
+fn query_pending_owner<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+) -> StdResult<PendingOwnerResponse> {
+    let state = config_read(&deps.storage).load()?;
+    Ok(PendingOwnerResponse {
+        pending_owner: state.pending_owner,
+    })
+}
+
+fn query_denoms<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+) -> StdResult<DenomsResponse> {
+    let state = config_read(&deps.storage).load()?;
+    let denoms = |coins: &[Coin]| coins.iter().map(|c| c.denom.clone()).collect();
+    Ok(DenomsResponse {
+        collateral_denoms: denoms(&state.collateral),
+        counter_offer_denoms: denoms(&effective_counter_offer(&deps.querier, &state)?),
+    })
+}
+
+fn query_effective_counter_offer<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+) -> StdResult<EffectiveCounterOfferResponse> {
+    let state = config_read(&deps.storage).load()?;
+    Ok(EffectiveCounterOfferResponse {
+        counter_offer: effective_counter_offer(&deps.querier, &state)?,
+    })
+}
+
+/// Describes the option at height, "unknown" if no height was recorded yet
+fn status(state: &State, height: Option<u64>) -> &'static str {
+    match height {
+        _ if state.frozen => "frozen",
+        Some(height) if height >= state.expires => "expired",
+        _ if state.pending_funding => "pending_funding",
+        Some(_) => "active",
+        None => "unknown",
+    }
+}
+
+fn query_nft_info<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+) -> StdResult<NftInfoResponse> {
+    let state = config_read(&deps.storage).load()?;
+    let status = status(&state, last_height_read(&deps.storage).may_load()?);
+    let attr = |trait_type: &str, value: String| Trait {
+        trait_type: trait_type.to_string(),
+        value,
+    };
+    Ok(NftInfoResponse {
+        name: state.metadata.name.clone(),
+        description: state.metadata.description.clone(),
+        attributes: vec![
+            attr("expires", state.expires.to_string()),
+            attr(
+                "collateral",
+                format_coins(&state.collateral, &CoinFormat::Compact)?,
+            ),
+            attr(
+                "counter_offer",
+                format_coins(
+                    &effective_counter_offer(&deps.querier, &state)?,
+                    &CoinFormat::Compact,
+                )?,
+            ),
+            attr("status", status.to_string()),
+        ],
+    })
+}
+
+fn query_simulate_execute<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    sender: HumanAddr,
+    funds: Vec<Coin>,
+    collateral_recipients: Option<Vec<(HumanAddr, u16)>>,
+) -> StdResult<SimulateExecuteResponse> {
+    let state = config_read(&deps.storage).load()?;
+    let contract = contract_addr_read(&deps.storage).load()?;
+    let height = current_height(&deps.storage)?;
+    if let Some(recipients) = &collateral_recipients {
+        check_recipients(&deps.api, recipients)?;
+    }
+    let res = settle_execute(
+        &deps.querier,
+        &state,
+        &contract,
+        &sender,
+        funds,
+        collateral_recipients.as_deref(),
+        height,
+    )?;
+    Ok(SimulateExecuteResponse {
+        messages: res.messages,
+        log: res.log,
+    })
+}
+
+fn query_expiry_warning<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    threshold_blocks: u64,
+) -> StdResult<ExpiryWarningResponse> {
+    let state = config_read(&deps.storage).load()?;
+    let height = current_height(&deps.storage)?;
+    let blocks_remaining = state.expires.saturating_sub(height);
+    Ok(ExpiryWarningResponse {
+        expiring_soon: blocks_remaining > 0 && blocks_remaining <= threshold_blocks,
+        blocks_remaining,
+    })
+}
+
+fn query_risk_params<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+) -> StdResult<RiskParamsResponse> {
+    let state = config_read(&deps.storage).load()?;
+    let current_ratio_bps = match state.oracle {
+        Some(_) => Some(collateral_ratio_bps(&deps.querier, &state)?),
+        None => None,
+    };
+    let liquidatable_now = match (state.maintenance_ratio_bps, current_ratio_bps) {
+        (Some(maintenance), Some(ratio)) => ratio < maintenance && !state.pending_funding,
+        _ => false,
+    };
+    Ok(RiskParamsResponse {
+        maintenance_ratio_bps: state.maintenance_ratio_bps,
+        oracle: state.oracle,
+        liquidatable_now,
+        current_ratio_bps,
+    })
+}
+
+fn query_schema_version() -> SchemaVersionResponse {
+    SchemaVersionResponse {
+        schema_version: SCHEMA_VERSION,
+        contract: CONTRACT_NAME.to_string(),
+        version: CONTRACT_VERSION.to_string(),
+    }
+}
+
+fn query_role<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    address: HumanAddr,
+) -> StdResult<RoleResponse> {
+    let state = config_read(&deps.storage).load()?;
+    let role = if address == state.creator {
+        Role::Creator
+    } else if address == state.owner {
+        Role::Owner
+    } else if state.beneficiary.as_ref() == Some(&address) {
+        Role::Beneficiary
+    } else {
+        Role::None
+    };
+    Ok(RoleResponse { role })
+}
+
+/// Block height for queries, as last recorded by handle_touch
+fn current_height<S: Storage>(storage: &S) -> StdResult<u64> {
+    last_height_read(storage)
+        .may_load()?
+        .ok_or_else(|| StdError::generic_err("no height recorded, call touch first"))
+}
+
+fn can_response(check: Result<(), (ReasonCode, StdError)>) -> CanResponse {
+    match check {
+        Ok(()) => CanResponse {
+            can: true,
+            reason: None,
+            reason_code: None,
+        },
+        Err((code, err)) => CanResponse {
+            can: false,
+            reason: Some(match err {
+                StdError::GenericErr { msg, .. } => msg,
+                e => e.to_string(),
+            }),
+            reason_code: Some(code),
+        },
+    }
+}
+
+fn query_can_execute<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    sender: HumanAddr,
+    funds: Vec<Coin>,
+) -> StdResult<CanResponse> {
+    let state = config_read(&deps.storage).load()?;
+    let height = current_height(&deps.storage)?;
+    Ok(can_response(check_execute(
+        &deps.querier,
+        &state,
+        &sender,
+        &funds,
+        height,
+    )))
+}
+
+fn query_can_burn<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    funds: Vec<Coin>,
+) -> StdResult<CanResponse> {
+    let state = config_read(&deps.storage).load()?;
+    let height = current_height(&deps.storage)?;
+    Ok(can_response(check_burn(&state, &funds, height)))
+}
+
+fn query_can_transfer<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    sender: HumanAddr,
+    recipient: HumanAddr,
+) -> StdResult<CanResponse> {
+    let state = config_read(&deps.storage).load()?;
+    let contract = contract_addr_read(&deps.storage).load()?;
+    Ok(can_response(check_transfer(
+        &deps.api, &state, &contract, &sender, &recipient,
+    )))
+}
+
+fn query_flat<S: Storage, A: Api, Q: Querier>(deps: &Extern<S, A, Q>) -> StdResult<FlatResponse> {
+    let state = config_read(&deps.storage).load()?;
+    let height = last_height_read(&deps.storage).may_load()?;
+    let id = contract_addr_read(&deps.storage).load()?;
+    let counter_offer = effective_counter_offer(&deps.querier, &state)?;
+    let values = vec![
+        id.to_string(),
+        state.creator.to_string(),
+        state.owner.to_string(),
+        status(&state, height).to_string(),
+        state.expires.to_string(),
+        format_coins(&state.collateral, &CoinFormat::Compact)?,
+        format_coins(&counter_offer, &CoinFormat::Compact)?,
+        state.created_height.to_string(),
+    ];
+    Ok(FlatResponse {
+        columns: FLAT_COLUMNS.iter().map(|c| c.to_string()).collect(),
+        values,
+    })
+}
+
+fn query_exercise_preview<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+) -> StdResult<ExercisePreviewResponse> {
+    let state = config_read(&deps.storage).load()?;
+    let contract = contract_addr_read(&deps.storage).load()?;
+    let height = current_height(&deps.storage)?;
+    let required_payment = effective_counter_offer(&deps.querier, &state)?;
+    let settlement = compute_settlement(
+        &deps.querier,
+        &state,
+        &contract,
+        &state.owner,
+        &required_payment,
+        None,
+        height,
+    )?;
+    let mut payments = settlement.payments.into_iter().map(|(_, amount)| amount);
+    let creator_payment = payments.next().unwrap_or_default();
+    let fee_payment = payments.next().unwrap_or_default();
+    let release = settlement.releases.into_iter().map(|(_, amount)| amount);
+    Ok(ExercisePreviewResponse {
+        owner_receives: normalize_coins(release.flatten().collect()),
+        creator_receives: normalize_coins([creator_payment, settlement.dust].concat()),
+        fee_collector_receives: fee_payment,
+        required_payment,
+    })
+}
+
+fn query_owner_pnl<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    spot_price: Uint128,
+    quote_denom: String,
+) -> StdResult<OwnerPnlResponse> {
+    let state = config_read(&deps.storage).load()?;
+    let counter_offer = effective_counter_offer(&deps.querier, &state)?;
+    let (received, paid) = match (state.collateral.as_slice(), counter_offer.as_slice()) {
+        ([received], [paid]) => (received, paid),
+        _ => {
+            return Err(StdError::generic_err(
+                "pnl needs a single denom on each leg",
+            ))
+        }
+    };
+
+    // one leg is in quote_denom, the other is the underlying priced at spot
+    let value = |coin: &Coin| -> StdResult<Uint128> {
+        coin.amount
+            .u128()
+            .checked_mul(spot_price.u128())
+            .map(Uint128)
+            .ok_or_else(|| StdError::generic_err("pnl overflows"))
+    };
+    let (received_value, paid_value) = if paid.denom == quote_denom {
+        (value(received)?, paid.amount)
+    } else if received.denom == quote_denom {
+        (received.amount, value(paid)?)
+    } else {
+        return Err(StdError::generic_err(format!(
+            "neither leg is in {}",
+            quote_denom
+        )));
+    };
+
+    Ok(match received_value - paid_value {
+        Ok(gain) => OwnerPnlResponse {
+            positive: true,
+            amount: gain,
+        },
+        Err(_) => OwnerPnlResponse {
+            positive: false,
+            amount: (paid_value - received_value)?,
+        },
+    })
+}
+
+fn query_parties<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+) -> StdResult<PartiesResponse> {
+    let state = config_read(&deps.storage).load()?;
+    Ok(PartiesResponse {
+        creator: state.creator,
+        owner: state.owner,
+        admin: state.admin,
+        beneficiary: state.beneficiary,
+        pending_owner: state.pending_owner,
+        proceeds_recipient: state.proceeds_recipient,
+        proceeds_callback: state.proceeds_callback.map(|(contract, _)| contract),
+        burn_recipient: state.burn_recipient,
+        referrer: state.referrer,
+    })
+}
+
+fn query_health_check<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+) -> StdResult<HealthCheckResponse> {
+    let state = config_read(&deps.storage).load()?;
+    let mut violations = vec![];
+
+    for (role, address) in &[("creator", &state.creator), ("owner", &state.owner)] {
+        if deps.api.canonical_address(address).is_err() {
+            violations.push(format!("{} is not a valid address", role));
+        }
+    }
+    if state.collateral.is_empty() && !state.pending_funding {
+        violations.push("collateral is empty".to_string());
+    }
+    if !state.collateral.is_empty() && state.pending_funding {
+        violations.push("collateral is set while pending funding".to_string());
+    }
+    match &state.counter_offer_ratio {
+        Some((_, _, 0)) => violations.push("counter_offer_ratio denominator is zero".to_string()),
+        Some(_) => {}
+        None if state.counter_offer.is_empty() => {
+            violations.push("counter_offer is empty".to_string())
+        }
+        None => {}
+    }
+    if state.collateral.len() > MAX_DENOMS || state.counter_offer.len() > MAX_DENOMS {
+        violations.push(format!("more than {} denoms", MAX_DENOMS));
+    }
+    // ForceExpire may expire it in the block it was created
+    if state.expires < state.created_height {
+        violations.push("expires is before created_height".to_string());
+    }
+    if state.referral_bps >= BPS_TOTAL {
+        violations.push(format!("referral_bps is not below {}", BPS_TOTAL));
+    }
+    if let Some(bounty) = &state.burn_bounty {
+        if !state.pending_funding && !shortfall(&state.collateral, bounty).is_empty() {
+            violations.push("burn_bounty exceeds collateral".to_string());
+        }
+    }
+
+    Ok(HealthCheckResponse {
+        healthy: violations.is_empty(),
+        violations,
+    })
+}
+
+fn query_operational_state<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+) -> StdResult<OperationalStateResponse> {
+    let state = config_read(&deps.storage).may_load()?;
+    let handlers_enabled = HANDLERS
+        .iter()
+        .filter(|handler| match &state {
+            Some(state) => !(state.frozen && FROZEN_HANDLERS.contains(handler)),
+            None => **handler == "touch",
+        })
+        .map(|handler| handler.to_string())
+        .collect();
+    Ok(OperationalStateResponse {
+        paused: matches!(&state, Some(state) if state.frozen),
+        admin: state.and_then(|state| state.admin),
+        handlers_enabled,
+    })
+}
+
+fn query_contract_info<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+) -> StdResult<ContractInfoResponse> {
+    let state = config_read(&deps.storage).may_load()?;
+    Ok(ContractInfoResponse {
+        contract: CONTRACT_NAME.to_string(),
+        version: CONTRACT_VERSION.to_string(),
+        admin: admin_read(&deps.storage).may_load()?.flatten(),
+        paused: matches!(&state, Some(state) if state.frozen),
+        total_options: if state.is_some() { 1 } else { 0 },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::oracle::{OracleQueryMsg, PriceResponse};
+    use crate::registry::{RegistryQueryMsg, TransferableResponse};
+    use crate::sanctions::{SanctionedResponse, SanctionsQueryMsg};
+    use crate::state::{StateV1, CONFIG_KEY};
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, MockQuerier, MOCK_CONTRACT_ADDR};
+    use cosmwasm_std::{
+        coin, coins, from_binary, from_slice, log, CosmosMsg, Empty, QuerierResult, QueryRequest,
+        WasmQuery,
+    };
+    use cosmwasm_storage::singleton;
+
+    /// Answers price queries to the oracle from a fixed table, and everything else from the mock
+    struct OracleQuerier {
+        base: MockQuerier,
+        oracle: HumanAddr,
+        prices: Vec<(&'static str, &'static str, Decimal)>,
+    }
+
+    impl Querier for OracleQuerier {
+        fn raw_query(&self, bin_request: &[u8]) -> QuerierResult {
+            let request: QueryRequest<Empty> = from_slice(bin_request).unwrap();
+            match &request {
+                QueryRequest::Wasm(WasmQuery::Smart { contract_addr, msg })
+                    if contract_addr == &self.oracle =>
+                {
+                    let OracleQueryMsg::Price { base, quote } = from_binary(msg).unwrap();
+                    let res = match self.prices.iter().find(|p| p.0 == base && p.1 == quote) {
+                        Some((_, _, price)) => to_binary(&PriceResponse { price: *price }),
+                        None => Err(StdError::not_found("price")),
+                    };
+                    Ok(res)
+                }
+                _ => self.base.raw_query(bin_request),
+            }
+        }
+    }
+
+    /// Answers transferable queries to the registry from a fixed list, and everything else from the mock
+    struct RegistryQuerier {
+        base: MockQuerier,
+        registry: HumanAddr,
+        transferable: Vec<&'static str>,
+    }
+
+    impl Querier for RegistryQuerier {
+        fn raw_query(&self, bin_request: &[u8]) -> QuerierResult {
+            let request: QueryRequest<Empty> = from_slice(bin_request).unwrap();
+            match &request {
+                QueryRequest::Wasm(WasmQuery::Smart { contract_addr, msg })
+                    if contract_addr == &self.registry =>
+                {
+                    let RegistryQueryMsg::Transferable { denom } = from_binary(msg).unwrap();
+                    Ok(to_binary(&TransferableResponse {
+                        transferable: self.transferable.contains(&denom.as_str()),
+                    }))
+                }
+                _ => self.base.raw_query(bin_request),
+            }
+        }
+    }
+
+    /// Answers sanctions queries from a fixed list, and everything else from the mock
+    struct SanctionsQuerier {
+        base: MockQuerier,
+        sanctions: HumanAddr,
+        listed: Vec<&'static str>,
+    }
+
+    impl Querier for SanctionsQuerier {
+        fn raw_query(&self, bin_request: &[u8]) -> QuerierResult {
+            let request: QueryRequest<Empty> = from_slice(bin_request).unwrap();
+            match &request {
+                QueryRequest::Wasm(WasmQuery::Smart { contract_addr, msg })
+                    if contract_addr == &self.sanctions =>
+                {
+                    let SanctionsQueryMsg::IsSanctioned { address } = from_binary(msg).unwrap();
+                    Ok(to_binary(&SanctionedResponse {
+                        sanctioned: self.listed.contains(&address.as_str()),
+                    }))
+                }
+                _ => self.base.raw_query(bin_request),
+            }
+        }
+    }
+
+    fn with_oracle(
+        base: MockQuerier,
+        prices: Vec<(&'static str, &'static str, Decimal)>,
+    ) -> OracleQuerier {
+        OracleQuerier {
+            base,
+            oracle: HumanAddr::from("oracle"),
+            prices,
+        }
+    }
+
+    /// Plain call option, with struct update for what a test varies
+    fn init_msg() -> InitMsg {
+        InitMsg {
+            counter_offer: coins(40, "ETH"),
+            counter_offer_ratio: None,
+            expires: 100_000,
+            option_type: OptionType::Call,
+            underlying: None,
+            min_lifetime: None,
+            dust_threshold: None,
+            settlement_denoms: None,
+            admin: None,
+            oracle: None,
+            label: None,
+            require_transferable_check: false,
+            denom_registry: None,
+            referral_bps: None,
+            coin_attr_format: CoinFormat::Compact,
+            maintenance_ratio_bps: None,
+            allow_deferred_funding: false,
+            sanctions: None,
+        }
+    }
+
+    #[test]
+    fn proper_initialization() {
+        let mut deps = mock_dependencies(20, &[]);
+
+        let msg = init_msg();
+        let env = mock_env("creator", &coins(1, "BTC"));
+
+        // we can just call .unwrap() to assert this was a success
+        let res = init(&mut deps, env, msg).unwrap();
+        assert_eq!(0, res.messages.len());
+
+        // it worked, let's query the state
+        let res = query_config(&deps).unwrap();
+        assert_eq!(100_000, res.expires);
+        assert_eq!("creator", res.owner.as_str());
+        assert_eq!("creator", res.creator.as_str());
+        assert_eq!(coins(1, "BTC"), res.collateral);
+        assert_eq!(coins(40, "ETH"), res.counter_offer);
+        assert_eq!(OptionType::Call, res.option_type);
+        assert_eq!(12_345, res.created_height);
+
+        let res = query_option_type(&deps).unwrap();
+        assert_eq!(OptionType::Call, res.option_type);
+    }
+
+    #[test]
+    fn transfer() {
+        let mut deps = mock_dependencies(20, &[]);
+
+        let msg = init_msg();
+        let env = mock_env("creator", &coins(1, "BTC"));
+
+        // we can just call .unwrap() to assert this was a success
+        let res = init(&mut deps, env, msg).unwrap();
+        assert_eq!(0, res.messages.len());
+
+        // random cannot transfer
+        let env = mock_env("anyone", &[]);
+        let err = handle_transfer(&mut deps, env, HumanAddr::from("anyone")).unwrap_err();
+        match err {
+            StdError::Unauthorized { .. } => {}
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // owner cannot transfer to self
+        let env = mock_env("creator", &[]);
+        let err = handle_transfer(&mut deps, env, HumanAddr::from("creator")).unwrap_err();
+        match err {
+            StdError::GenericErr { msg, .. } => {
+                assert_eq!("cannot transfer to current owner", msg.as_str())
+            }
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // owner cannot transfer to the contract
+        let env = mock_env("creator", &[]);
+        let err = handle_transfer(&mut deps, env, HumanAddr::from(MOCK_CONTRACT_ADDR)).unwrap_err();
+        match err {
+            StdError::GenericErr { msg, .. } => {
+                assert_eq!("cannot transfer to the contract itself", msg.as_str())
+            }
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // owner cannot attach funds to a transfer
+        let env = mock_env("creator", &coins(5, "ETH"));
+        let err = handle_transfer(&mut deps, env, HumanAddr::from("someone")).unwrap_err();
+        match err {
+            StdError::GenericErr { msg, .. } => {
+                assert_eq!("don't send funds with transfer", msg.as_str())
+            }
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // owner can transfer
+        let env = mock_env("creator", &[]);
+        let res = handle_transfer(&mut deps, env, HumanAddr::from("someone")).unwrap();
+        assert_eq!(res.log.len(), 2);
+        assert_eq!(res.log[0], log("action", "transfer"));
+
+        // check updated properly
+        let res = query_config(&deps).unwrap();
+        assert_eq!("someone", res.owner.as_str());
+        assert_eq!("creator", res.creator.as_str());
+    }
+
+    #[test]
+    fn execute() {
+        let mut deps = mock_dependencies(20, &coins(1, "BTC"));
+
+        let counter_offer = coins(40, "ETH");
+        let collateral = coins(1, "BTC");
+        let msg = InitMsg {
+            counter_offer: counter_offer.clone(),
+            ..init_msg()
+        };
+        let env = mock_env("creator", &collateral);
+
+        // we can just call .unwrap() to assert this was a success
+        let _ = init(&mut deps, env, msg).unwrap();
+
+        // set new owner
+        let env = mock_env("creator", &[]);
+        let _ = handle_transfer(&mut deps, env, HumanAddr::from("owner")).unwrap();
+
+        // random cannot execute
+        let env = mock_env("creator", &counter_offer);
+        let err = handle_execute(&mut deps, env, None, None, None, None).unwrap_err();
+        match err {
+            StdError::Unauthorized { .. } => {}
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // expired cannot execute
+        let mut env = mock_env("owner", &counter_offer);
+        env.block.height = 200_000;
+        let err = handle_execute(&mut deps, env, None, None, None, None).unwrap_err();
+        match err {
+            StdError::GenericErr { msg, .. } => assert_eq!("option expired", msg.as_str()),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // bad counter_offer cannot execute
+        let env = mock_env("owner", &coins(39, "ETH"));
+        let err = handle_execute(&mut deps, env, None, None, None, None).unwrap_err();
+        match err {
+            StdError::GenericErr { msg, .. } => assert!(msg.contains("counter offer")),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // proper execution
+        let env = mock_env("owner", &counter_offer);
+        let res = handle_execute(&mut deps, env, None, None, None, None).unwrap();
+        assert_eq!(res.messages.len(), 2);
+        assert_eq!(
+            res.messages[0],
+            CosmosMsg::Bank(BankMsg::Send {
+                from_address: MOCK_CONTRACT_ADDR.into(),
+                to_address: "creator".into(),
+                amount: counter_offer,
+            })
+        );
+        assert_eq!(
+            res.messages[1],
+            CosmosMsg::Bank(BankMsg::Send {
+                from_address: MOCK_CONTRACT_ADDR.into(),
+                to_address: "owner".into(),
+                amount: collateral,
+            })
+        );
+
+        assert_eq!(res.log[1], log("option_type", "call"));
+
+        // check deleted
+        let _ = query_config(&deps).unwrap_err();
+        assert!(!query_is_active(&deps).unwrap().active);
+    }
+
+    #[test]
+    fn execute_put() {
+        let mut deps = mock_dependencies(20, &coins(40, "ETH"));
+
+        // the writer locks the strike (counter_offer), the owner may sell the underlying for it
+        let strike = coins(40, "ETH");
+        let underlying = coins(1, "BTC");
+        let msg = InitMsg {
+            counter_offer: strike.clone(),
+            option_type: OptionType::Put,
+            underlying: Some(underlying.clone()),
+            ..init_msg()
+        };
+
+        // the creator must lock exactly the counter_offer
+        let env = mock_env("creator", &coins(39, "ETH"));
+        match init(&mut deps, env, msg.clone()) {
+            Err(StdError::GenericErr { msg, .. }) => {
+                assert_eq!("a put must send exactly the counter_offer", msg.as_str())
+            }
+            e => panic!("unexpected result: {:?}", e),
+        }
+        // and name what the owner delivers
+        let env = mock_env("creator", &strike);
+        let no_underlying = InitMsg {
+            underlying: None,
+            ..msg.clone()
+        };
+        match init(&mut deps, env, no_underlying) {
+            Err(StdError::GenericErr { msg, .. }) => {
+                assert_eq!("a put must set underlying", msg.as_str())
+            }
+            e => panic!("unexpected result: {:?}", e),
+        }
+        // which a call has no use for
+        let env = mock_env("creator", &underlying);
+        let call = InitMsg {
+            option_type: OptionType::Call,
+            ..msg.clone()
+        };
+        match init(&mut deps, env, call) {
+            Err(StdError::GenericErr { msg, .. }) => {
+                assert_eq!("underlying is only used by a put", msg.as_str())
+            }
+            e => panic!("unexpected result: {:?}", e),
+        }
+
+        let env = mock_env("creator", &strike);
+        let _ = init(&mut deps, env, msg).unwrap();
+        let state = query_config(&deps).unwrap();
+        assert_eq!(state.option_type, OptionType::Put);
+        assert_eq!(state.collateral, strike);
+        assert_eq!(state.counter_offer, underlying);
+        assert_eq!(
+            query_option_type(&deps).unwrap().option_type,
+            OptionType::Put
+        );
+
+        let env = mock_env("creator", &[]);
+        let _ = handle_transfer(&mut deps, env, HumanAddr::from("owner")).unwrap();
+
+        // owner delivers the underlying and receives the strike
+        let env = mock_env("owner", &underlying);
+        let res = handle_execute(&mut deps, env, None, None, None, None).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![
+                CosmosMsg::Bank(BankMsg::Send {
+                    from_address: MOCK_CONTRACT_ADDR.into(),
+                    to_address: "creator".into(),
+                    amount: underlying,
+                }),
+                CosmosMsg::Bank(BankMsg::Send {
+                    from_address: MOCK_CONTRACT_ADDR.into(),
+                    to_address: "owner".into(),
+                    amount: strike,
+                }),
+            ]
+        );
+        assert_eq!(res.log[1], log("option_type", "put"));
+    }
+
+    #[test]
+    fn burn() {
+        let mut deps = mock_dependencies(20, &coins(1, "BTC"));
+
+        let counter_offer = coins(40, "ETH");
+        let collateral = coins(1, "BTC");
+        let msg = InitMsg {
+            counter_offer: counter_offer.clone(),
+            ..init_msg()
+        };
+        let env = mock_env("creator", &collateral);
+
+        // we can just call .unwrap() to assert this was a success
+        let _ = init(&mut deps, env, msg).unwrap();
+
+        // set new owner
+        let env = mock_env("creator", &[]);
+        let _ = handle_transfer(&mut deps, env, HumanAddr::from("owner")).unwrap();
+
+        // non-expired cannot execute
+        let env = mock_env("anyone", &[]);
+        let err = handle_burn(&mut deps, env).unwrap_err();
+        match err {
+            StdError::GenericErr { msg, .. } => assert_eq!("option not yet expired", msg.as_str()),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // with funds cannot execute
+        let mut env = mock_env("anyone", &counter_offer);
+        env.block.height = 200_000;
+        let err = handle_burn(&mut deps, env).unwrap_err();
+        match err {
+            StdError::GenericErr { msg, .. } => {
+                assert_eq!("don't send funds with burn", msg.as_str())
+            }
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // expired returns funds
+        let mut env = mock_env("anyone", &[]);
+        env.block.height = 200_000;
+        let res = handle_burn(&mut deps, env).unwrap();
+        assert_eq!(res.messages.len(), 1);
+        assert_eq!(
+            res.messages[0],
+            CosmosMsg::Bank(BankMsg::Send {
+                from_address: MOCK_CONTRACT_ADDR.into(),
+                to_address: "creator".into(),
+                amount: collateral,
+            })
+        );
+
+        // check deleted
+        let _ = query_config(&deps).unwrap_err();
+    }
+
+    #[test]
+    fn force_expire() {
+        let mut deps = mock_dependencies(20, &coins(1, "BTC"));
+
+        let counter_offer = coins(40, "ETH");
+        let collateral = coins(1, "BTC");
+        let msg = InitMsg {
+            counter_offer: counter_offer.clone(),
+            admin: Some(HumanAddr::from("admin")),
+            ..init_msg()
+        };
+        let env = mock_env("creator", &collateral);
+        let _ = init(&mut deps, env, msg).unwrap();
+
+        // set new owner
+        let env = mock_env("creator", &[]);
+        let _ = handle_transfer(&mut deps, env, HumanAddr::from("owner")).unwrap();
+
+        // non-admins cannot force expire
+        for sender in &["creator", "owner", "anyone"] {
+            let env = mock_env(*sender, &[]);
+            let err = handle_force_expire(&mut deps, env).unwrap_err();
+            match err {
+                StdError::Unauthorized { .. } => {}
+                e => panic!("unexpected error: {}", e),
+            }
+        }
+
+        // admin can force expire, without moving funds
+        let mut env = mock_env("admin", &[]);
+        env.block.height = 50_000;
+        let res = handle_force_expire(&mut deps, env).unwrap();
+        assert_eq!(res.messages.len(), 0);
+        assert_eq!(res.log[0], log("action", "force_expire"));
+        let res = query_config(&deps).unwrap();
+        assert_eq!(50_000, res.expires);
+
+        // cannot execute anymore
+        let mut env = mock_env("owner", &counter_offer);
+        env.block.height = 50_000;
+        let err = handle_execute(&mut deps, env, None, None, None, None).unwrap_err();
+        match err {
+            StdError::GenericErr { msg, .. } => assert_eq!("option expired", msg.as_str()),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // but can burn right away
+        let mut env = mock_env("anyone", &[]);
+        env.block.height = 50_000;
+        let res = handle_burn(&mut deps, env).unwrap();
+        assert_eq!(
+            res.messages[0],
+            CosmosMsg::Bank(BankMsg::Send {
+                from_address: MOCK_CONTRACT_ADDR.into(),
+                to_address: "creator".into(),
+                amount: collateral,
+            })
+        );
+    }
+
+    #[test]
+    fn force_expire_without_admin() {
+        let mut deps = mock_dependencies(20, &[]);
+
+        let msg = init_msg();
+        let env = mock_env("creator", &coins(1, "BTC"));
+        let _ = init(&mut deps, env, msg).unwrap();
+
+        let env = mock_env("creator", &[]);
+        let err = handle_force_expire(&mut deps, env).unwrap_err();
+        match err {
+            StdError::Unauthorized { .. } => {}
+            e => panic!("unexpected error: {}", e),
+        }
+    }
+
+    #[test]
+    fn is_active() {
+        let mut deps = mock_dependencies(20, &coins(1, "BTC"));
+
+        // no option yet
+        assert!(!query_is_active(&deps).unwrap().active);
+
+        let msg = init_msg();
+        let env = mock_env("creator", &coins(1, "BTC"));
+        let _ = init(&mut deps, env, msg).unwrap();
+        assert!(query_is_active(&deps).unwrap().active);
+
+        // still active (burnable) once expired
+        let mut env = mock_env("anyone", &[]);
+        env.block.height = 200_000;
+        assert!(query_is_active(&deps).unwrap().active);
+
+        // burned options are no longer active
+        let _ = handle_burn(&mut deps, env).unwrap();
+        assert!(!query_is_active(&deps).unwrap().active);
+
+        // nor are they until funded
+        let msg = InitMsg {
+            allow_deferred_funding: true,
+            ..init_msg()
+        };
+        let env = mock_env("creator", &[]);
+        let _ = init(&mut deps, env, msg).unwrap();
+        assert!(!query_is_active(&deps).unwrap().active);
+        let env = mock_env("creator", &coins(1, "BTC"));
+        let _ = handle_fund_collateral(&mut deps, env).unwrap();
+        assert!(query_is_active(&deps).unwrap().active);
+    }
+
+    #[test]
+    fn counter_offer_ratio() {
+        let mut deps = mock_dependencies(20, &coins(10, "BTC")).change_querier(|base| {
+            with_oracle(
+                base,
+                vec![("BTC", "ETH", Decimal::from_ratio(20u128, 1u128))],
+            )
+        });
+
+        // cannot mix fixed counter_offer and ratio
+        let msg = InitMsg {
+            counter_offer_ratio: Some(("ETH".to_string(), 6, 5)),
+            oracle: Some(HumanAddr::from("oracle")),
+            ..init_msg()
+        };
+        let env = mock_env("creator", &coins(10, "BTC"));
+        let err = init(&mut deps, env, msg).unwrap_err();
+        match err {
+            StdError::GenericErr { msg, .. } => assert!(msg.contains("both")),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // zero denominator is invalid
+        let msg = InitMsg {
+            counter_offer: vec![],
+            counter_offer_ratio: Some(("ETH".to_string(), 6, 0)),
+            oracle: Some(HumanAddr::from("oracle")),
+            ..init_msg()
+        };
+        let env = mock_env("creator", &coins(10, "BTC"));
+        let err = init(&mut deps, env, msg).unwrap_err();
+        match err {
+            StdError::GenericErr { msg, .. } => assert!(msg.contains("denominator")),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // ratio needs a single collateral denom
+        let msg = InitMsg {
+            counter_offer: vec![],
+            counter_offer_ratio: Some(("ETH".to_string(), 6, 5)),
+            oracle: Some(HumanAddr::from("oracle")),
+            ..init_msg()
+        };
+        let env = mock_env("creator", &[coin(10, "BTC"), coin(5, "ATOM")]);
+        let err = init(&mut deps, env, msg).unwrap_err();
+        match err {
+            StdError::GenericErr { msg, .. } => assert!(msg.contains("single denom")),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // the collateral is valued by the oracle
+        let msg = InitMsg {
+            counter_offer: vec![],
+            counter_offer_ratio: Some(("ETH".to_string(), 6, 5)),
+            ..init_msg()
+        };
+        let env = mock_env("creator", &coins(10, "BTC"));
+        let err = init(&mut deps, env, msg.clone()).unwrap_err();
+        match err {
+            StdError::GenericErr { msg, .. } => {
+                assert_eq!("counter_offer_ratio requires an oracle", msg.as_str())
+            }
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // 1.2x the collateral value: 10 BTC at 20 ETH/BTC is worth 200 ETH
+        let msg = InitMsg {
+            oracle: Some(HumanAddr::from("oracle")),
+            ..msg
+        };
+        let env = mock_env("creator", &coins(10, "BTC"));
+        let _ = init(&mut deps, env, msg).unwrap();
+        let state = query_config(&deps).unwrap();
+        assert_eq!(
+            effective_counter_offer(&deps.querier, &state).unwrap(),
+            coins(240, "ETH")
+        );
+        let res = query_effective_counter_offer(&deps).unwrap();
+        assert_eq!(res.counter_offer, coins(240, "ETH"));
+
+        // 1.2x the collateral amount is not enough
+        let env = mock_env("creator", &coins(12, "ETH"));
+        let err = handle_execute(&mut deps, env, None, None, None, None).unwrap_err();
+        match err {
+            StdError::GenericErr { msg, .. } => assert!(msg.contains("counter offer")),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // the computed amount executes
+        let env = mock_env("creator", &coins(240, "ETH"));
+        let res = handle_execute(&mut deps, env, None, None, None, None).unwrap();
+        assert_eq!(
+            res.messages[0],
+            CosmosMsg::Bank(BankMsg::Send {
+                from_address: MOCK_CONTRACT_ADDR.into(),
+                to_address: "creator".into(),
+                amount: coins(240, "ETH"),
+            })
+        );
+    }
+
+    #[test]
+    fn collateral_value() {
+        let mut deps = mock_dependencies(20, &[]).change_querier(|base| {
+            with_oracle(
+                base,
+                vec![
+                    ("BTC", "USD", Decimal::percent(1_000_000)),
+                    ("ETH", "USD", Decimal::percent(40_000)),
+                ],
+            )
+        });
+
+        let msg = InitMsg {
+            oracle: Some(HumanAddr::from("oracle")),
+            ..init_msg()
+        };
+        let env = mock_env(
+            "creator",
+            &[coin(2, "BTC"), coin(5, "ETH"), coin(30, "USD")],
+        );
+        let _ = init(&mut deps, env, msg).unwrap();
+
+        // 2 * 10_000 + 5 * 400 + 30
+        let res = query_collateral_value(&deps, "USD".to_string()).unwrap();
+        assert_eq!(res.value, Uint128(22_030));
+        assert_eq!(res.quote_denom, "USD");
+
+        // no price for BTC in EUR
+        let err = query_collateral_value(&deps, "EUR".to_string()).unwrap_err();
+        match err {
+            StdError::GenericErr { msg, .. } => assert_eq!("no price for BTC in EUR", msg.as_str()),
+            e => panic!("unexpected error: {}", e),
+        }
+    }
+
+    #[test]
+    fn touch() {
+        let mut deps = mock_dependencies(20, &[]);
+
+        // never touched
+        assert_eq!(query_last_height(&deps).unwrap().height, None);
+
+        // anyone can touch
+        let mut env = mock_env("anyone", &[]);
+        env.block.height = 123_456;
+        let res = handle_touch(&mut deps, env).unwrap();
+        assert_eq!(res.log[0], log("action", "touch"));
+        assert_eq!(query_last_height(&deps).unwrap().height, Some(123_456));
+
+        // and it moves along with later touches
+        let mut env = mock_env("someone", &[]);
+        env.block.height = 123_500;
+        let _ = handle_touch(&mut deps, env).unwrap();
+        assert_eq!(query_last_height(&deps).unwrap().height, Some(123_500));
+    }
+
+    #[test]
+    fn can_execute_and_burn() {
+        let mut deps = mock_dependencies(20, &[]);
+
+        let counter_offer = coins(40, "ETH");
+        let msg = InitMsg {
+            counter_offer: counter_offer.clone(),
+            ..init_msg()
+        };
+        let env = mock_env("creator", &coins(1, "BTC"));
+        let _ = init(&mut deps, env, msg).unwrap();
+
+        // needs a recorded height
+        let _ = query_can_burn(&deps, vec![]).unwrap_err();
+        let mut env = mock_env("anyone", &[]);
+        env.block.height = 50_000;
+        let _ = handle_touch(&mut deps, env).unwrap();
+
+        // owner with the counter_offer can execute
+        let res = query_can_execute(&deps, "creator".into(), counter_offer.clone()).unwrap();
+        assert!(res.can);
+        assert_eq!(res.reason_code, None);
+        assert_eq!(res.reason, None);
+
+        let res = query_can_execute(&deps, "anyone".into(), counter_offer.clone()).unwrap();
+        assert!(!res.can);
+        assert_eq!(res.reason_code, Some(ReasonCode::NotOwner));
+
+        let res = query_can_execute(&deps, "creator".into(), coins(39, "ETH")).unwrap();
+        assert!(!res.can);
+        assert_eq!(res.reason_code, Some(ReasonCode::WrongFunds));
+        assert!(res.reason.unwrap().contains("counter offer"));
+
+        let res = query_can_burn(&deps, vec![]).unwrap();
+        assert!(!res.can);
+        assert_eq!(res.reason_code, Some(ReasonCode::NotExpired));
+        assert_eq!(res.reason, Some("option not yet expired".to_string()));
+
+        // once expired, flips around
+        let mut env = mock_env("anyone", &[]);
+        env.block.height = 100_000;
+        let _ = handle_touch(&mut deps, env).unwrap();
+
+        let res = query_can_execute(&deps, "creator".into(), counter_offer.clone()).unwrap();
+        assert!(!res.can);
+        assert_eq!(res.reason_code, Some(ReasonCode::Expired));
+
+        let res = query_can_burn(&deps, counter_offer).unwrap();
+        assert!(!res.can);
+        assert_eq!(res.reason_code, Some(ReasonCode::UnexpectedFunds));
+
+        let res = query_can_burn(&deps, vec![]).unwrap();
+        assert!(res.can);
+    }
+
+    #[test]
+    fn min_lifetime() {
+        let mut deps = mock_dependencies(20, &[]);
+        let env = mock_env("creator", &coins(1, "BTC"));
+        let height = env.block.height;
+
+        // just below the minimum lifetime
+        let msg = InitMsg {
+            expires: height + 9,
+            min_lifetime: Some(10),
+            ..init_msg()
+        };
+        let err = init(&mut deps, env.clone(), msg).unwrap_err();
+        match err {
+            StdError::GenericErr { msg, .. } => {
+                assert_eq!("Option must live at least 10 blocks", msg.as_str())
+            }
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // exactly the minimum lifetime
+        let msg = InitMsg {
+            expires: height + 10,
+            min_lifetime: Some(10),
+            ..init_msg()
+        };
+        let _ = init(&mut deps, env.clone(), msg).unwrap();
+
+        // default rejects expiry in the creation block
+        let msg = InitMsg {
+            expires: height,
+            ..init_msg()
+        };
+        let _ = init(&mut deps, env.clone(), msg).unwrap_err();
+
+        // and accepts the next block
+        let msg = InitMsg {
+            expires: height + 1,
+            ..init_msg()
+        };
+        let _ = init(&mut deps, env, msg).unwrap();
+    }
+
+    #[test]
+    fn set_metadata() {
+        let mut deps = mock_dependencies(20, &[]);
+
+        let msg = init_msg();
+        let env = mock_env("creator", &coins(1, "BTC"));
+        let _ = init(&mut deps, env, msg).unwrap();
+        assert_eq!(query_config(&deps).unwrap().metadata, Metadata::default());
+
+        // hand it to an owner
+        let env = mock_env("creator", &[]);
+        let _ = handle_transfer(&mut deps, env, HumanAddr::from("owner")).unwrap();
+
+        // only the creator can set metadata
+        let env = mock_env("owner", &[]);
+        let err = handle_set_metadata(&mut deps, env, Some("mine".into()), None).unwrap_err();
+        match err {
+            StdError::Unauthorized { .. } => {}
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // too long is rejected
+        let env = mock_env("creator", &[]);
+        let name = "n".repeat(MAX_NAME_LEN + 1);
+        let err = handle_set_metadata(&mut deps, env, Some(name), None).unwrap_err();
+        match err {
+            StdError::GenericErr { msg, .. } => assert_eq!("name longer than 64 bytes", msg),
+            e => panic!("unexpected error: {}", e),
+        }
+        let env = mock_env("creator", &[]);
+        let description = "d".repeat(MAX_DESCRIPTION_LEN + 1);
+        let err = handle_set_metadata(&mut deps, env, None, Some(description)).unwrap_err();
+        match err {
+            StdError::GenericErr { msg, .. } => {
+                assert_eq!("description longer than 512 bytes", msg)
+            }
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // creator can set it, up to the limits
+        let env = mock_env("creator", &[]);
+        let name = "n".repeat(MAX_NAME_LEN);
+        let res = handle_set_metadata(
+            &mut deps,
+            env,
+            Some(name.clone()),
+            Some("BTC call".to_string()),
+        )
+        .unwrap();
+        assert_eq!(res.log[0], log("action", "set_metadata"));
+        assert_eq!(
+            query_config(&deps).unwrap().metadata,
+            Metadata {
+                name: Some(name),
+                description: Some("BTC call".to_string()),
+            }
+        );
+    }
+
+    #[test]
+    fn burn_insufficient_balance() {
+        // contract holds less than the recorded collateral
+        let mut deps = mock_dependencies(20, &[coin(1, "BTC"), coin(5, "ATOM")]);
+
+        let msg = init_msg();
+        let env = mock_env("creator", &[coin(1, "BTC"), coin(7, "ATOM")]);
+        let _ = init(&mut deps, env, msg).unwrap();
+
+        let mut env = mock_env("anyone", &[]);
+        env.block.height = 200_000;
+        let err = handle_burn(&mut deps, env).unwrap_err();
+        match err {
+            StdError::GenericErr { msg, .. } => {
+                assert_eq!("insufficient contract balance: need 7ATOM, have 5ATOM", msg)
+            }
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // option is still there
+        assert!(query_is_active(&deps).unwrap().active);
+    }
+
+    #[test]
+    fn solvency() {
+        let mut deps = mock_dependencies(20, &[coin(1, "BTC"), coin(5, "ATOM")]);
+
+        let msg = init_msg();
+        let env = mock_env("creator", &[coin(1, "BTC"), coin(7, "ATOM")]);
+        let _ = init(&mut deps, env, msg).unwrap();
+
+        let res = query_solvency(&deps).unwrap();
+        assert!(!res.solvent);
+        assert_eq!(res.shortfall, coins(2, "ATOM"));
+
+        // topped up
+        deps.querier
+            .update_balance(MOCK_CONTRACT_ADDR, vec![coin(1, "BTC"), coin(8, "ATOM")]);
+        let res = query_solvency(&deps).unwrap();
+        assert!(res.solvent);
+        assert_eq!(res.shortfall, vec![]);
+    }
+
+    #[test]
+    fn beneficiary() {
+        let mut deps = mock_dependencies(20, &[]);
+
+        let msg = init_msg();
+        let env = mock_env("creator", &coins(1, "BTC"));
+        let _ = init(&mut deps, env, msg).unwrap();
+
+        let env = mock_env("creator", &[]);
+        let _ = handle_transfer(&mut deps, env, HumanAddr::from("owner")).unwrap();
+
+        // only the owner can set a beneficiary
+        let env = mock_env("creator", &[]);
+        let err = handle_set_beneficiary(&mut deps, env, Some("heir".into())).unwrap_err();
+        match err {
+            StdError::Unauthorized { .. } => {}
+            e => panic!("unexpected error: {}", e),
+        }
+        // must be a valid address
+        let env = mock_env("owner", &[]);
+        let _ = handle_set_beneficiary(&mut deps, env, Some("x".into())).unwrap_err();
+        assert_eq!(query_config(&deps).unwrap().beneficiary, None);
+        let env = mock_env("owner", &[]);
+        let res = handle_set_beneficiary(&mut deps, env, Some("heir".into())).unwrap();
+        assert_eq!(res.log[1], log("beneficiary", "heir"));
+        assert_eq!(
+            query_config(&deps).unwrap().beneficiary,
+            Some(HumanAddr::from("heir"))
+        );
+
+        // beneficiary must wait for expiry
+        let env = mock_env("heir", &[]);
+        let err = handle_claim_as_beneficiary(&mut deps, env).unwrap_err();
+        match err {
+            StdError::GenericErr { msg, .. } => assert_eq!("option not yet expired", msg),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // others cannot claim
+        let mut env = mock_env("anyone", &[]);
+        env.block.height = 200_000;
+        let err = handle_claim_as_beneficiary(&mut deps, env).unwrap_err();
+        match err {
+            StdError::Unauthorized { .. } => {}
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // beneficiary takes ownership after expiry, without moving funds
+        let mut env = mock_env("heir", &[]);
+        env.block.height = 200_000;
+        let res = handle_claim_as_beneficiary(&mut deps, env).unwrap();
+        assert_eq!(res.messages.len(), 0);
+        assert_eq!(res.log[0], log("action", "claim_as_beneficiary"));
+        let state = query_config(&deps).unwrap();
+        assert_eq!(state.owner, HumanAddr::from("heir"));
+        assert_eq!(state.beneficiary, None);
+    }
+
+    #[test]
+    fn transfer_clears_beneficiary() {
+        let mut deps = mock_dependencies(20, &[]);
+
+        let msg = init_msg();
+        let env = mock_env("creator", &coins(1, "BTC"));
+        let _ = init(&mut deps, env, msg).unwrap();
+
+        let env = mock_env("creator", &[]);
+        let _ = handle_set_beneficiary(&mut deps, env, Some("heir".into())).unwrap();
+        let env = mock_env("creator", &[]);
+        let _ = handle_transfer(&mut deps, env, HumanAddr::from("owner")).unwrap();
+        assert_eq!(query_config(&deps).unwrap().beneficiary, None);
+    }
+
+    #[test]
+    fn claim_as_beneficiary_clears_pending_owner() {
+        let mut deps = mock_dependencies(20, &[]);
+
+        let msg = init_msg();
+        let env = mock_env("creator", &coins(1, "BTC"));
+        let _ = init(&mut deps, env, msg).unwrap();
+
+        let env = mock_env("creator", &[]);
+        let _ = handle_set_beneficiary(&mut deps, env, Some("heir".into())).unwrap();
+        let env = mock_env("creator", &[]);
+        let _ = handle_offer_transfer(&mut deps, env, "buyer".into()).unwrap();
+
+        let mut env = mock_env("heir", &[]);
+        env.block.height = 100_000;
+        let _ = handle_claim_as_beneficiary(&mut deps, env).unwrap();
+        assert_eq!(query_config(&deps).unwrap().pending_owner, None);
+
+        // the stale offer cannot take ownership from the beneficiary
+        let env = mock_env("buyer", &[]);
+        let err = handle_accept_ownership(&mut deps, env).unwrap_err();
+        match err {
+            StdError::Unauthorized { .. } => {}
+            e => panic!("unexpected error: {}", e),
+        }
+        assert_eq!(query_config(&deps).unwrap().owner, HumanAddr::from("heir"));
+    }
+
+    #[test]
+    fn execute_expected_counter_offer() {
+        let mut deps = mock_dependencies(20, &coins(1, "BTC"));
+
+        let counter_offer = coins(40, "ETH");
+        let msg = InitMsg {
+            counter_offer: counter_offer.clone(),
+            ..init_msg()
+        };
+        let env = mock_env("creator", &coins(1, "BTC"));
+        let _ = init(&mut deps, env, msg).unwrap();
+
+        // mismatch is rejected, even with the right funds
+        let env = mock_env("creator", &counter_offer);
+        let err =
+            handle_execute(&mut deps, env, Some(coins(30, "ETH")), None, None, None).unwrap_err();
+        match err {
+            StdError::GenericErr { msg, .. } => {
+                assert_eq!("counter offer differs from expected_counter_offer", msg)
+            }
+            e => panic!("unexpected error: {}", e),
+        }
+        assert!(query_is_active(&deps).unwrap().active);
+
+        // match proceeds
+        let env = mock_env("creator", &counter_offer);
+        let res = handle_execute(
+            &mut deps,
+            env,
+            Some(counter_offer.clone()),
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        assert_eq!(res.messages.len(), 2);
+    }
+
+    #[test]
+    fn execute_max_price_and_deadline() {
+        let mut deps = mock_dependencies(20, &coins(10, "BTC")).change_querier(|base| {
+            with_oracle(
+                base,
+                vec![
+                    ("BTC", "ETH", Decimal::from_ratio(20u128, 1u128)),
+                    ("ETH", "USD", Decimal::from_ratio(400u128, 1u128)),
+                ],
+            )
+        });
+
+        // the oracle quotes 240 ETH for the ratio
+        let msg = InitMsg {
+            counter_offer: vec![],
+            counter_offer_ratio: Some(("ETH".to_string(), 6, 5)),
+            oracle: Some(HumanAddr::from("oracle")),
+            ..init_msg()
+        };
+        let env = mock_env("creator", &coins(10, "BTC"));
+        let _ = init(&mut deps, env, msg).unwrap();
+
+        let env = mock_env("creator", &coins(240, "ETH"));
+        let err = handle_execute(&mut deps, env, None, None, Some(Uint128(239)), None).unwrap_err();
+        match err {
+            StdError::GenericErr { msg, .. } => {
+                assert_eq!("required payment 240 exceeds max_price 239", msg.as_str())
+            }
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // mock_env is at height 12_345
+        let env = mock_env("creator", &coins(240, "ETH"));
+        let err = handle_execute(&mut deps, env, None, None, None, Some(12_344)).unwrap_err();
+        match err {
+            StdError::GenericErr { msg, .. } => {
+                assert_eq!("execute deadline passed", msg.as_str())
+            }
+            e => panic!("unexpected error: {}", e),
+        }
+
+        let env = mock_env("creator", &coins(240, "ETH"));
+        let res =
+            handle_execute(&mut deps, env, None, None, Some(Uint128(240)), Some(12_345)).unwrap();
+        assert_eq!(res.log[0], log("action", "execute"));
+
+        // paying in a settlement denom bounds its oracle-quoted amount
+        let mut deps = mock_dependencies(20, &coins(1, "BTC")).change_querier(|base| {
+            with_oracle(
+                base,
+                vec![("ETH", "USD", Decimal::from_ratio(400u128, 1u128))],
+            )
+        });
+        let msg = InitMsg {
+            settlement_denoms: Some(vec!["USD".to_string()]),
+            oracle: Some(HumanAddr::from("oracle")),
+            ..init_msg()
+        };
+        let env = mock_env("creator", &coins(1, "BTC"));
+        let _ = init(&mut deps, env, msg).unwrap();
+
+        let env = mock_env("creator", &coins(16_000, "USD"));
+        let err =
+            handle_execute(&mut deps, env, None, None, Some(Uint128(15_999)), None).unwrap_err();
+        match err {
+            StdError::GenericErr { msg, .. } => {
+                assert_eq!(
+                    "required payment 16000 exceeds max_price 15999",
+                    msg.as_str()
+                )
+            }
+            e => panic!("unexpected error: {}", e),
+        }
+        let env = mock_env("creator", &coins(16_000, "USD"));
+        let _ = handle_execute(&mut deps, env, None, None, Some(Uint128(16_000)), None).unwrap();
+    }
+
+    #[test]
+    fn execute_expected_counter_offer_any_order() {
+        let mut deps = mock_dependencies(20, &coins(1, "BTC"));
+
+        let msg = InitMsg {
+            counter_offer: vec![coin(40, "ETH"), coin(2_000, "USD")],
+            ..init_msg()
+        };
+        let env = mock_env("creator", &coins(1, "BTC"));
+        let _ = init(&mut deps, env, msg).unwrap();
+
+        // the same coins, reordered and split, still match
+        let expected = vec![coin(1_000, "USD"), coin(40, "ETH"), coin(1_000, "USD")];
+        let env = mock_env("creator", &[coin(2_000, "USD"), coin(40, "ETH")]);
+        let res = handle_execute(&mut deps, env, Some(expected), None, None, None).unwrap();
+        assert_eq!(res.messages.len(), 2);
+    }
+
+    #[test]
+    fn execute_dust_threshold() {
+        // (collateral, owner receives, creator receives as dust)
+        let cases = vec![
+            (99, vec![], coins(99, "BTC")),
+            (100, coins(100, "BTC"), vec![]),
+        ];
+        for (amount, release, dust) in cases {
+            let mut deps = mock_dependencies(20, &[coin(amount, "BTC"), coin(5_000, "ATOM")]);
+
+            let counter_offer = coins(40, "ETH");
+            let msg = InitMsg {
+                counter_offer: counter_offer.clone(),
+                dust_threshold: Some(vec![coin(100, "BTC"), coin(1_000, "ATOM")]),
+                ..init_msg()
+            };
+            let env = mock_env("creator", &[coin(amount, "BTC"), coin(5_000, "ATOM")]);
+            let _ = init(&mut deps, env, msg).unwrap();
+
+            let env = mock_env("creator", &[]);
+            let _ = handle_transfer(&mut deps, env, HumanAddr::from("owner")).unwrap();
+
+            let env = mock_env("owner", &counter_offer);
+            let res = handle_execute(&mut deps, env, None, None, None, None).unwrap();
+            let mut expected = vec![
+                CosmosMsg::Bank(BankMsg::Send {
+                    from_address: MOCK_CONTRACT_ADDR.into(),
+                    to_address: "creator".into(),
+                    amount: counter_offer,
+                }),
+                CosmosMsg::Bank(BankMsg::Send {
+                    from_address: MOCK_CONTRACT_ADDR.into(),
+                    to_address: "owner".into(),
+                    amount: [coins(5_000, "ATOM"), release].concat(),
+                }),
+            ];
+            if !dust.is_empty() {
+                expected.push(CosmosMsg::Bank(BankMsg::Send {
+                    from_address: MOCK_CONTRACT_ADDR.into(),
+                    to_address: "creator".into(),
+                    amount: dust,
+                }));
+            }
+            assert_eq!(res.messages, expected);
+        }
+    }
+
+    #[test]
+    fn settlement_denoms_validation() {
+        let mut deps = mock_dependencies(20, &coins(1, "BTC")).change_querier(|base| {
+            with_oracle(base, vec![("ETH", "USD", Decimal::percent(40_000))])
+        });
+
+        // settlement denoms need an oracle to convert
+        let msg = InitMsg {
+            settlement_denoms: Some(vec!["USD".to_string()]),
+            ..init_msg()
+        };
+        let env = mock_env("creator", &coins(1, "BTC"));
+        let _ = init(&mut deps, env, msg).unwrap_err();
+
+        let msg = InitMsg {
+            settlement_denoms: Some(vec!["USD".to_string()]),
+            oracle: Some(HumanAddr::from("oracle")),
+            ..init_msg()
+        };
+        let env = mock_env("creator", &coins(1, "BTC"));
+        let _ = init(&mut deps, env, msg).unwrap();
+
+        // not a settlement denom
+        let env = mock_env("creator", &coins(1_000_000, "ATOM"));
+        let err = handle_execute(&mut deps, env, None, None, None, None).unwrap_err();
+        match err {
+            StdError::GenericErr { msg, .. } => assert!(msg.contains("exact counter offer")),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // not enough value
+        let env = mock_env("creator", &coins(15_999, "USD"));
+        let err = handle_execute(&mut deps, env, None, None, None, None).unwrap_err();
+        match err {
+            StdError::GenericErr { msg, .. } => {
+                assert_eq!("must send at least 16000USD for the counter offer", msg)
+            }
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // the counter_offer in the native denom is still fine
+        let env = mock_env("creator", &coins(40, "ETH"));
+        let _ = handle_execute(&mut deps, env, None, None, None, None).unwrap();
+    }
+
+    #[test]
+    fn execute_in_settlement_denom() {
+        let mut deps = mock_dependencies(20, &coins(1, "BTC")).change_querier(|base| {
+            with_oracle(base, vec![("ETH", "USD", Decimal::percent(40_000))])
+        });
+
+        let msg = InitMsg {
+            settlement_denoms: Some(vec!["USD".to_string()]),
+            oracle: Some(HumanAddr::from("oracle")),
+            ..init_msg()
+        };
+        let env = mock_env("creator", &coins(1, "BTC"));
+        let _ = init(&mut deps, env, msg).unwrap();
+        let env = mock_env("creator", &[]);
+        let _ = handle_transfer(&mut deps, env, HumanAddr::from("owner")).unwrap();
+
+        // creator gets paid in the settlement denom
+        let env = mock_env("owner", &coins(16_000, "USD"));
+        let res = handle_execute(&mut deps, env, None, None, None, None).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![
+                CosmosMsg::Bank(BankMsg::Send {
+                    from_address: MOCK_CONTRACT_ADDR.into(),
+                    to_address: "creator".into(),
+                    amount: coins(16_000, "USD"),
+                }),
+                CosmosMsg::Bank(BankMsg::Send {
+                    from_address: MOCK_CONTRACT_ADDR.into(),
+                    to_address: "owner".into(),
+                    amount: coins(1, "BTC"),
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn lifecycle() {
+        let mut deps = mock_dependencies(20, &[]);
+
+        let msg = init_msg();
+        let mut env = mock_env("creator", &coins(1, "BTC"));
+        env.block.height = 1_000;
+        let _ = init(&mut deps, env, msg).unwrap();
+
+        let res = query_lifecycle(&deps).unwrap();
+        assert_eq!(
+            res,
+            LifecycleResponse {
+                created_height: 1_000,
+                exercise_from: None,
+                expires: 100_000,
+                grace_until: None,
+                now: None,
+            }
+        );
+
+        let mut env = mock_env("anyone", &[]);
+        env.block.height = 5_000;
+        let _ = handle_touch(&mut deps, env).unwrap();
+        assert_eq!(query_lifecycle(&deps).unwrap().now, Some(5_000));
+    }
+
+    #[test]
+    fn max_denoms() {
+        let mut deps = mock_dependencies(20, &[]);
+
+        let basket = |n: usize, amount: u128| -> Vec<Coin> {
+            (0..n)
+                .map(|i| coin(amount, &format!("denom{}", i)))
+                .collect()
+        };
+
+        // too many collateral denoms
+        let msg = init_msg();
+        let env = mock_env("creator", &basket(MAX_DENOMS + 1, 1));
+        let err = init(&mut deps, env, msg).unwrap_err();
+        match err {
+            StdError::GenericErr { msg, .. } => assert_eq!("Cannot use more than 8 denoms", msg),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // too many counter_offer denoms
+        let msg = InitMsg {
+            counter_offer: basket(MAX_DENOMS + 1, 2),
+            ..init_msg()
+        };
+        let env = mock_env("creator", &coins(1, "BTC"));
+        let _ = init(&mut deps, env, msg).unwrap_err();
+
+        // exactly at the cap works and settles in one send per side
+        let collateral = basket(MAX_DENOMS, 1);
+        let counter_offer = basket(MAX_DENOMS, 2);
+        let msg = InitMsg {
+            counter_offer: counter_offer.clone(),
+            ..init_msg()
+        };
+        let env = mock_env("creator", &collateral);
+        let _ = init(&mut deps, env, msg).unwrap();
+
+        deps.querier
+            .update_balance(MOCK_CONTRACT_ADDR, collateral.clone());
+        let env = mock_env("creator", &counter_offer);
+        let res = handle_execute(&mut deps, env, None, None, None, None).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![
+                CosmosMsg::Bank(BankMsg::Send {
+                    from_address: MOCK_CONTRACT_ADDR.into(),
+                    to_address: "creator".into(),
+                    amount: counter_offer,
+                }),
+                CosmosMsg::Bank(BankMsg::Send {
+                    from_address: MOCK_CONTRACT_ADDR.into(),
+                    to_address: "creator".into(),
+                    amount: collateral,
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn breakeven() {
+        let mut deps = mock_dependencies(20, &[]).change_querier(|base| {
+            with_oracle(base, vec![("ETH", "USD", Decimal::percent(40_000))])
+        });
+
+        let msg = InitMsg {
+            oracle: Some(HumanAddr::from("oracle")),
+            ..init_msg()
+        };
+        let env = mock_env("creator", &coins(2, "BTC"));
+        let _ = init(&mut deps, env, msg).unwrap();
+
+        // 16_000 USD for 2 BTC
+        let res = query_breakeven(&deps, "USD".to_string()).unwrap();
+        assert_eq!(res.price, Decimal::percent(800_000));
+        assert_eq!(res.quote_denom, "USD");
+
+        // unpriced
+        let _ = query_breakeven(&deps, "EUR".to_string()).unwrap_err();
+    }
+
+    #[test]
+    fn breakeven_basket() {
+        let mut deps = mock_dependencies(20, &[]).change_querier(|base| with_oracle(base, vec![]));
+
+        let msg = InitMsg {
+            counter_offer: coins(40, "USD"),
+            oracle: Some(HumanAddr::from("oracle")),
+            ..init_msg()
+        };
+        let env = mock_env("creator", &[coin(2, "BTC"), coin(3, "ETH")]);
+        let _ = init(&mut deps, env, msg).unwrap();
+
+        let err = query_breakeven(&deps, "USD".to_string()).unwrap_err();
+        match err {
+            StdError::GenericErr { msg, .. } => {
+                assert_eq!("breakeven requires collateral in a single denom", msg)
+            }
+            e => panic!("unexpected error: {}", e),
+        }
+    }
+
+    #[test]
+    fn two_step_transfer() {
+        let mut deps = mock_dependencies(20, &[]);
+
+        let msg = init_msg();
+        let env = mock_env("creator", &coins(1, "BTC"));
+        let _ = init(&mut deps, env, msg).unwrap();
+
+        // only the owner can offer
+        let env = mock_env("anyone", &[]);
+        let err = handle_offer_transfer(&mut deps, env, "anyone".into()).unwrap_err();
+        match err {
+            StdError::Unauthorized { .. } => {}
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // same rules as a direct transfer
+        let env = mock_env("creator", &[]);
+        let err = handle_offer_transfer(&mut deps, env, MOCK_CONTRACT_ADDR.into()).unwrap_err();
+        match err {
+            StdError::GenericErr { msg, .. } => {
+                assert_eq!("cannot transfer to the contract itself", msg.as_str())
+            }
+            e => panic!("unexpected error: {}", e),
+        }
+        let env = mock_env("creator", &coins(5, "ETH"));
+        let err = handle_offer_transfer(&mut deps, env, "someone".into()).unwrap_err();
+        match err {
+            StdError::GenericErr { msg, .. } => {
+                assert_eq!("don't send funds with transfer", msg.as_str())
+            }
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // offer, then cancel
+        let env = mock_env("creator", &[]);
+        let res = handle_offer_transfer(&mut deps, env, "wrong".into()).unwrap();
+        assert_eq!(res.log[1], log("pending_owner", "wrong"));
+        let state = query_config(&deps).unwrap();
+        assert_eq!(state.owner, HumanAddr::from("creator"));
+        assert_eq!(state.pending_owner, Some(HumanAddr::from("wrong")));
+
+        let env = mock_env("creator", &[]);
+        let _ = handle_cancel_transfer(&mut deps, env).unwrap();
+        assert_eq!(query_config(&deps).unwrap().pending_owner, None);
+
+        // cancelled offer cannot be accepted
+        let env = mock_env("wrong", &[]);
+        let err = handle_accept_ownership(&mut deps, env).unwrap_err();
+        match err {
+            StdError::Unauthorized { .. } => {}
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // offer, then accept
+        assert_eq!(query_pending_owner(&deps).unwrap().pending_owner, None);
+        let env = mock_env("creator", &[]);
+        let _ = handle_offer_transfer(&mut deps, env, "someone".into()).unwrap();
+        assert_eq!(
+            query_pending_owner(&deps).unwrap().pending_owner,
+            Some(HumanAddr::from("someone"))
+        );
+
+        // only the pending owner can accept, without funds
+        let env = mock_env("anyone", &[]);
+        let err = handle_accept_ownership(&mut deps, env).unwrap_err();
+        match err {
+            StdError::Unauthorized { .. } => {}
+            e => panic!("unexpected error: {}", e),
+        }
+        let env = mock_env("someone", &coins(5, "ETH"));
+        let err = handle_accept_ownership(&mut deps, env).unwrap_err();
+        match err {
+            StdError::GenericErr { msg, .. } => {
+                assert_eq!("don't send funds with transfer", msg.as_str())
+            }
+            e => panic!("unexpected error: {}", e),
+        }
+
+        let env = mock_env("someone", &[]);
+        let res = handle_accept_ownership(&mut deps, env).unwrap();
+        assert_eq!(res.log[0], log("action", "accept_ownership"));
+        let state = query_config(&deps).unwrap();
+        assert_eq!(state.owner, HumanAddr::from("someone"));
+        assert_eq!(state.pending_owner, None);
+        assert_eq!(state.creator, HumanAddr::from("creator"));
+        assert_eq!(query_pending_owner(&deps).unwrap().pending_owner, None);
+    }
+
+    #[test]
+    fn normalized_coins() {
+        let mut deps = mock_dependencies(20, &[coin(1, "AAA"), coin(5, "ZZZ")]);
+
+        let msg = InitMsg {
+            counter_offer: vec![coin(40, "ETH"), coin(0, "ATOM"), coin(10, "BTC")],
+            ..init_msg()
+        };
+        let env = mock_env("creator", &[coin(5, "ZZZ"), coin(0, "BTC"), coin(1, "AAA")]);
+        let _ = init(&mut deps, env, msg).unwrap();
+
+        let state = query_config(&deps).unwrap();
+        assert_eq!(state.collateral, vec![coin(1, "AAA"), coin(5, "ZZZ")]);
+        assert_eq!(state.counter_offer, vec![coin(10, "BTC"), coin(40, "ETH")]);
+
+        // the counter_offer executes in any order, with duplicate denoms summed
+        let funds = vec![coin(30, "ETH"), coin(10, "BTC"), coin(10, "ETH")];
+        let env = mock_env("creator", &funds);
+        let res = handle_execute(&mut deps, env, None, None, None, None).unwrap();
+        assert_eq!(
+            res.messages[0],
+            CosmosMsg::Bank(BankMsg::Send {
+                from_address: MOCK_CONTRACT_ADDR.into(),
+                to_address: "creator".into(),
+                amount: vec![coin(10, "BTC"), coin(40, "ETH")],
+            })
+        );
+    }
+
+    #[test]
+    fn normalize_coins_merges_denoms() {
+        let coins = vec![
+            coin(100, "earth"),
+            coin(7, "atom"),
+            coin(0, "moon"),
+            coin(100, "earth"),
+        ];
+        assert_eq!(
+            normalize_coins(coins),
+            vec![coin(7, "atom"), coin(200, "earth")]
+        );
+        assert_eq!(normalize_coins(vec![coin(0, "earth")]), vec![]);
+    }
+
+    #[test]
+    fn reissue() {
+        let mut deps = mock_dependencies(20, &coins(1, "BTC"));
+
+        let msg = InitMsg {
+            min_lifetime: Some(1_000),
+            ..init_msg()
+        };
+        let env = mock_env("creator", &coins(1, "BTC"));
+        let _ = init(&mut deps, env, msg).unwrap();
+
+        let env = mock_env("creator", &[]);
+        let _ = handle_transfer(&mut deps, env, HumanAddr::from("owner")).unwrap();
+
+        // cannot pull a live option from its owner
+        let env = mock_env("creator", &[]);
+        let err = handle_reissue(&mut deps, env, 300_000, coins(50, "ETH")).unwrap_err();
+        match err {
+            StdError::GenericErr { msg, .. } => assert_eq!("option not yet expired", msg),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // only the creator can reissue
+        let mut env = mock_env("owner", &[]);
+        env.block.height = 200_000;
+        let err = handle_reissue(&mut deps, env, 300_000, coins(50, "ETH")).unwrap_err();
+        match err {
+            StdError::Unauthorized { .. } => {}
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // new expiry must be in the future
+        let mut env = mock_env("creator", &[]);
+        env.block.height = 200_000;
+        let _ = handle_reissue(&mut deps, env, 200_000, coins(50, "ETH")).unwrap_err();
+
+        // and at least the min_lifetime set at init away
+        let mut env = mock_env("creator", &[]);
+        env.block.height = 200_000;
+        let err = handle_reissue(&mut deps, env, 200_999, coins(50, "ETH")).unwrap_err();
+        match err {
+            StdError::GenericErr { msg, .. } => {
+                assert_eq!("Option must live at least 1000 blocks", msg)
+            }
+            e => panic!("unexpected error: {}", e),
+        }
+        let mut env = mock_env("creator", &[]);
+        env.block.height = 200_000;
+        let _ = handle_reissue(&mut deps, env, 201_000, coins(50, "ETH")).unwrap();
+
+        // roll the expired option into fresh terms, without moving collateral
+        let mut env = mock_env("creator", &[]);
+        env.block.height = 200_000;
+        let res = handle_reissue(&mut deps, env, 300_000, coins(50, "ETH")).unwrap();
+        assert_eq!(res.messages.len(), 0);
+        assert_eq!(res.log[0], log("action", "reissue"));
+
+        let state = query_config(&deps).unwrap();
+        assert_eq!(state.owner, HumanAddr::from("creator"));
+        assert_eq!(state.expires, 300_000);
+        assert_eq!(state.counter_offer, coins(50, "ETH"));
+        assert_eq!(state.collateral, coins(1, "BTC"));
+        assert_eq!(state.created_height, 200_000);
+
+        // and it can be executed on the new terms
+        let mut env = mock_env("creator", &coins(50, "ETH"));
+        env.block.height = 200_001;
+        let _ = handle_execute(&mut deps, env, None, None, None, None).unwrap();
+    }
+
+    #[test]
+    fn proceeds_recipient() {
+        let mut deps = mock_dependencies(20, &coins(1, "BTC"));
+
+        let counter_offer = coins(40, "ETH");
+        let msg = InitMsg {
+            counter_offer: counter_offer.clone(),
+            ..init_msg()
+        };
+        let env = mock_env("creator", &coins(1, "BTC"));
+        let _ = init(&mut deps, env, msg).unwrap();
+
+        let env = mock_env("creator", &[]);
+        let _ = handle_transfer(&mut deps, env, HumanAddr::from("owner")).unwrap();
+
+        // only the creator can set it
+        let env = mock_env("owner", &[]);
+        let err = handle_set_proceeds_recipient(&mut deps, env, "owner".into()).unwrap_err();
+        match err {
+            StdError::Unauthorized { .. } => {}
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // must be a valid address
+        let env = mock_env("creator", &[]);
+        let _ = handle_set_proceeds_recipient(&mut deps, env, "x".into()).unwrap_err();
+
+        // changed mid-life, the latest one wins
+        let env = mock_env("creator", &[]);
+        let _ = handle_set_proceeds_recipient(&mut deps, env, "treasury".into()).unwrap();
+        let env = mock_env("creator", &[]);
+        let _ = handle_set_proceeds_recipient(&mut deps, env, "new_treasury".into()).unwrap();
+
+        let env = mock_env("owner", &counter_offer);
+        let res = handle_execute(&mut deps, env, None, None, None, None).unwrap();
+        assert_eq!(
+            res.messages[0],
+            CosmosMsg::Bank(BankMsg::Send {
+                from_address: MOCK_CONTRACT_ADDR.into(),
+                to_address: "new_treasury".into(),
+                amount: counter_offer,
+            })
+        );
+    }
+
+    #[test]
+    fn execute_refuses_over_release() {
+        let mut deps = mock_dependencies(20, &coins(1, "BTC"));
+
+        let counter_offer = coins(40, "ETH");
+        let msg = InitMsg {
+            counter_offer: counter_offer.clone(),
+            ..init_msg()
+        };
+        let env = mock_env("creator", &coins(1, "BTC"));
+        let _ = init(&mut deps, env, msg).unwrap();
+
+        // corrupt the recorded collateral beyond what the contract holds
+        let mut state = query_config(&deps).unwrap();
+        state.collateral = coins(1_000, "BTC");
+        config(&mut deps.storage).save(&state).unwrap();
+
+        let env = mock_env("creator", &counter_offer);
+        let err = handle_execute(&mut deps, env, None, None, None, None).unwrap_err();
+        match err {
+            StdError::GenericErr { msg, .. } => {
+                assert_eq!("invalid release amount: exceeds contract balance", msg)
+            }
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // a zero entry is not canonical collateral either
+        state.collateral = vec![coin(0, "ATOM"), coin(1, "BTC")];
+        config(&mut deps.storage).save(&state).unwrap();
+
+        let env = mock_env("creator", &counter_offer);
+        let err = handle_execute(&mut deps, env, None, None, None, None).unwrap_err();
+        match err {
+            StdError::GenericErr { msg, .. } => {
+                assert_eq!(
+                    "invalid release amount: does not match recorded collateral",
+                    msg
+                )
+            }
+            e => panic!("unexpected error: {}", e),
+        }
+        assert!(query_is_active(&deps).unwrap().active);
+    }
+
+    #[test]
+    fn denoms() {
+        let mut deps = mock_dependencies(20, &[]);
+
+        let msg = InitMsg {
+            counter_offer: vec![coin(40, "ETH"), coin(3, "BTC")],
+            ..init_msg()
+        };
+        let env = mock_env("creator", &[coin(1, "BTC"), coin(7, "ATOM")]);
+        let _ = init(&mut deps, env, msg).unwrap();
+
+        let res = query_denoms(&deps).unwrap();
+        assert_eq!(res.collateral_denoms, vec!["ATOM", "BTC"]);
+        assert_eq!(res.counter_offer_denoms, vec!["BTC", "ETH"]);
+    }
+
+    #[test]
+    fn donate() {
+        let mut deps = mock_dependencies(20, &[]);
+
+        let msg = init_msg();
+        let env = mock_env("creator", &coins(1, "BTC"));
+        let _ = init(&mut deps, env, msg).unwrap();
+
+        // cannot donate nothing
+        let env = mock_env("sponsor", &[]);
+        let err = handle_donate(&mut deps, env).unwrap_err();
+        match err {
+            StdError::GenericErr { msg, .. } => {
+                assert_eq!("must send funds to donate", msg.as_str())
+            }
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // anyone can donate, merging into existing denoms
+        let env = mock_env("sponsor", &[coin(2, "BTC"), coin(50, "ATOM")]);
+        let res = handle_donate(&mut deps, env).unwrap();
+        assert_eq!(0, res.messages.len());
+        assert_eq!(res.log[0], log("action", "donate"));
+        assert_eq!(res.log[1], log("donor", "sponsor"));
+        assert_eq!(res.log[2], log("amount", "50ATOM,2BTC"));
+
+        let res = query_config(&deps).unwrap();
+        assert_eq!(res.collateral, vec![coin(50, "ATOM"), coin(3, "BTC")]);
+        assert_eq!(res.owner, HumanAddr::from("creator"));
+    }
+
+    #[test]
+    fn donate_rejected() {
+        let mut deps = mock_dependencies(20, &[]);
+
+        // it would change a ratio counter_offer
+        let msg = InitMsg {
+            counter_offer: vec![],
+            counter_offer_ratio: Some(("ETH".to_string(), 40, 1)),
+            oracle: Some(HumanAddr::from("oracle")),
+            ..init_msg()
+        };
+        let env = mock_env("creator", &coins(1, "BTC"));
+        let _ = init(&mut deps, env, msg).unwrap();
+        let env = mock_env("sponsor", &coins(1, "BTC"));
+        let err = handle_donate(&mut deps, env).unwrap_err();
+        match err {
+            StdError::GenericErr { msg, .. } => {
+                assert_eq!(
+                    "cannot donate to a counter_offer_ratio option",
+                    msg.as_str()
+                )
+            }
+            e => panic!("unexpected error: {}", e),
+        }
+        assert_eq!(query_config(&deps).unwrap().collateral, coins(1, "BTC"));
+
+        // or come before the creator's collateral
+        let msg = InitMsg {
+            allow_deferred_funding: true,
+            ..init_msg()
+        };
+        let env = mock_env("creator", &[]);
+        let _ = init(&mut deps, env, msg).unwrap();
+        let env = mock_env("sponsor", &coins(1, "JUNK"));
+        let err = handle_donate(&mut deps, env).unwrap_err();
+        match err {
+            StdError::GenericErr { msg, .. } => {
+                assert_eq!("option awaiting collateral", msg.as_str())
+            }
+            e => panic!("unexpected error: {}", e),
+        }
+        assert_eq!(query_config(&deps).unwrap().collateral, vec![]);
+    }
+
+    #[test]
+    fn label() {
+        let mut deps = mock_dependencies(20, &[]);
+
+        // over-length label is rejected
+        let msg = InitMsg {
+            label: Some("l".repeat(MAX_LABEL_LEN + 1)),
+            ..init_msg()
+        };
+        let env = mock_env("creator", &coins(1, "BTC"));
+        let err = init(&mut deps, env, msg).unwrap_err();
+        match err {
+            StdError::GenericErr { msg, .. } => {
+                assert_eq!("label longer than 128 bytes", msg.as_str())
+            }
+            e => panic!("unexpected error: {}", e),
+        }
+
+        let msg = InitMsg {
+            label: Some("BTC/ETH call".to_string()),
+            ..init_msg()
+        };
+        let env = mock_env("creator", &coins(1, "BTC"));
+        let _ = init(&mut deps, env, msg).unwrap();
+
+        let res = query_config(&deps).unwrap();
+        assert_eq!(res.label, Some("BTC/ETH call".to_string()));
+    }
+
+    #[test]
+    fn execute_after_expiry_reverts() {
+        let mut deps = mock_dependencies(20, &coins(1, "BTC"));
+
+        let msg = init_msg();
+        let env = mock_env("creator", &coins(1, "BTC"));
+        let _ = init(&mut deps, env, msg).unwrap();
+        let before = query_config(&deps).unwrap();
+
+        // an error, not a refund message: the tx reverts and the funds stay with the sender
+        let mut env = mock_env("creator", &coins(40, "ETH"));
+        env.block.height = 100_000;
+        let err = handle(
+            &mut deps,
+            env,
+            HandleMsg::Execute {
+                expected_counter_offer: None,
+                collateral_recipients: None,
+                max_price: None,
+                deadline: None,
+            },
+        )
+        .unwrap_err();
+        match err {
+            StdError::GenericErr { msg, .. } => assert_eq!("option expired", msg.as_str()),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // option is untouched and can still be burned
+        assert_eq!(query_config(&deps).unwrap(), before);
+        let mut env = mock_env("anyone", &[]);
+        env.block.height = 100_000;
+        let _ = handle_burn(&mut deps, env).unwrap();
+    }
+
+    #[test]
+    fn shorten_expiry() {
+        let mut deps = mock_dependencies(20, &[]);
+
+        let msg = init_msg();
+        let env = mock_env("creator", &coins(1, "BTC"));
+        let _ = init(&mut deps, env, msg).unwrap();
+        let env = mock_env("creator", &[]);
+        let _ = handle_transfer(&mut deps, env, HumanAddr::from("owner")).unwrap();
+
+        // only the creator proposes, and only earlier
+        let env = mock_env("owner", &[]);
+        let err = handle_propose_shorten(&mut deps, env, 50_000).unwrap_err();
+        match err {
+            StdError::Unauthorized { .. } => {}
+            e => panic!("unexpected error: {}", e),
+        }
+        let env = mock_env("creator", &[]);
+        let err = handle_propose_shorten(&mut deps, env, 100_000).unwrap_err();
+        match err {
+            StdError::GenericErr { msg, .. } => {
+                assert_eq!("new_expires must be before expires", msg.as_str())
+            }
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // the owner has nothing to accept yet
+        let env = mock_env("owner", &[]);
+        let err = handle_accept_shorten(&mut deps, env).unwrap_err();
+        match err {
+            StdError::GenericErr { msg, .. } => assert_eq!("no proposed expiry", msg.as_str()),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // a proposal alone does not change the expiry
+        let env = mock_env("creator", &[]);
+        let _ = handle_propose_shorten(&mut deps, env, 50_000).unwrap();
+        let res = query_config(&deps).unwrap();
+        assert_eq!(res.expires, 100_000);
+        assert_eq!(res.proposed_expires, Some(50_000));
+
+        // the creator cannot accept on the owner's behalf
+        let env = mock_env("creator", &[]);
+        let err = handle_accept_shorten(&mut deps, env).unwrap_err();
+        match err {
+            StdError::Unauthorized { .. } => {}
+            e => panic!("unexpected error: {}", e),
+        }
+
+        let env = mock_env("owner", &[]);
+        let res = handle_accept_shorten(&mut deps, env).unwrap();
+        assert_eq!(res.log[0], log("action", "accept_shorten"));
+        let res = query_config(&deps).unwrap();
+        assert_eq!(res.expires, 50_000);
+        assert_eq!(res.proposed_expires, None);
+    }
+
+    #[test]
+    fn force_expire_drops_shorten_proposal() {
+        let mut deps = mock_dependencies(20, &[]);
+
+        let msg = InitMsg {
+            admin: Some(HumanAddr::from("admin")),
+            ..init_msg()
+        };
+        let env = mock_env("creator", &coins(1, "BTC"));
+        let _ = init(&mut deps, env, msg).unwrap();
+        let env = mock_env("creator", &[]);
+        let _ = handle_transfer(&mut deps, env, HumanAddr::from("owner")).unwrap();
+        let env = mock_env("creator", &[]);
+        let _ = handle_propose_shorten(&mut deps, env, 50_000).unwrap();
+
+        // the emergency expiry clears the proposal, so it cannot be accepted afterwards
+        let env = mock_env("admin", &[]);
+        let _ = handle_force_expire(&mut deps, env).unwrap();
+        let res = query_config(&deps).unwrap();
+        assert_eq!(res.expires, 12_345);
+        assert_eq!(res.proposed_expires, None);
+        let env = mock_env("owner", &[]);
+        let err = handle_accept_shorten(&mut deps, env).unwrap_err();
+        match err {
+            StdError::GenericErr { msg, .. } => assert_eq!("no proposed expiry", msg.as_str()),
+            e => panic!("unexpected error: {}", e),
+        }
+        assert_eq!(query_config(&deps).unwrap().expires, 12_345);
+
+        // and a proposal no earlier than expiry is never applied
+        let mut state = query_config(&deps).unwrap();
+        state.expires = 40_000;
+        state.proposed_expires = Some(50_000);
+        config(&mut deps.storage).save(&state).unwrap();
+        let env = mock_env("owner", &[]);
+        let err = handle_accept_shorten(&mut deps, env).unwrap_err();
+        match err {
+            StdError::GenericErr { msg, .. } => {
+                assert_eq!("new_expires must be before expires", msg.as_str())
+            }
+            e => panic!("unexpected error: {}", e),
+        }
+        assert_eq!(query_config(&deps).unwrap().expires, 40_000);
+    }
+
+    #[test]
+    fn execute_basket() {
+        let collateral = vec![coin(5, "ATOM"), coin(1, "BTC"), coin(300, "OSMO")];
+        let counter_offer = vec![coin(40, "ETH"), coin(2_000, "USD")];
+        let mut deps = mock_dependencies(20, &collateral);
+
+        let msg = InitMsg {
+            counter_offer: counter_offer.clone(),
+            ..init_msg()
+        };
+        let env = mock_env("creator", &collateral);
+        let _ = init(&mut deps, env, msg).unwrap();
+        let env = mock_env("creator", &[]);
+        let _ = handle_transfer(&mut deps, env, HumanAddr::from("owner")).unwrap();
+
+        // paying only some of the legs is rejected, nothing settles
+        for funds in &[coins(40, "ETH"), vec![coin(40, "ETH"), coin(1_999, "USD")]] {
+            let env = mock_env("owner", funds);
+            let err = handle_execute(&mut deps, env, None, None, None, None).unwrap_err();
+            match err {
+                StdError::GenericErr { msg, .. } => assert!(msg.contains("counter offer")),
+                e => panic!("unexpected error: {}", e),
+            }
+        }
+        assert!(query_is_active(&deps).unwrap().active);
+
+        // every leg settles in the one execute
+        let env = mock_env("owner", &counter_offer);
+        let res = handle_execute(&mut deps, env, None, None, None, None).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![
+                CosmosMsg::Bank(BankMsg::Send {
+                    from_address: MOCK_CONTRACT_ADDR.into(),
+                    to_address: "creator".into(),
+                    amount: counter_offer,
+                }),
+                CosmosMsg::Bank(BankMsg::Send {
+                    from_address: MOCK_CONTRACT_ADDR.into(),
+                    to_address: "owner".into(),
+                    amount: collateral,
+                }),
+            ]
+        );
+        assert!(!query_is_active(&deps).unwrap().active);
+    }
+
+    #[test]
+    fn effective_counter_offer_fixed() {
+        let mut deps = mock_dependencies(20, &[]);
+
+        let msg = init_msg();
+        let env = mock_env("creator", &coins(1, "BTC"));
+        let _ = init(&mut deps, env, msg).unwrap();
+
+        let res = query_effective_counter_offer(&deps).unwrap();
+        assert_eq!(res.counter_offer, coins(40, "ETH"));
+    }
+
+    #[test]
+    fn nft_info() {
+        let mut deps = mock_dependencies(20, &[]);
+
+        let msg = InitMsg {
+            counter_offer: vec![coin(40, "ETH"), coin(10, "USD")],
+            ..init_msg()
+        };
+        let env = mock_env("creator", &coins(1, "BTC"));
+        let _ = init(&mut deps, env, msg).unwrap();
+        let env = mock_env("creator", &[]);
+        let _ = handle_set_metadata(&mut deps, env, Some("BTC call".to_string()), None).unwrap();
+
+        let attributes = |status: &str| {
+            vec![
+                Trait {
+                    trait_type: "expires".to_string(),
+                    value: "100000".to_string(),
+                },
+                Trait {
+                    trait_type: "collateral".to_string(),
+                    value: "1BTC".to_string(),
+                },
+                Trait {
+                    trait_type: "counter_offer".to_string(),
+                    value: "40ETH,10USD".to_string(),
+                },
+                Trait {
+                    trait_type: "status".to_string(),
+                    value: status.to_string(),
+                },
+            ]
+        };
+
+        let res = query_nft_info(&deps).unwrap();
+        assert_eq!(res.name, Some("BTC call".to_string()));
+        assert_eq!(res.description, None);
+        assert_eq!(res.attributes, attributes("unknown"));
+
+        let env = mock_env("anyone", &[]);
+        let _ = handle_touch(&mut deps, env).unwrap();
+        assert_eq!(
+            query_nft_info(&deps).unwrap().attributes,
+            attributes("active")
+        );
+
+        let mut env = mock_env("anyone", &[]);
+        env.block.height = 100_000;
+        let _ = handle_touch(&mut deps, env).unwrap();
+        assert_eq!(
+            query_nft_info(&deps).unwrap().attributes,
+            attributes("expired")
+        );
+    }
+
+    #[test]
+    fn simulate_execute() {
+        let mut deps = mock_dependencies(20, &[coin(1, "BTC"), coin(5, "ATOM")]);
+
+        let msg = InitMsg {
+            dust_threshold: Some(coins(10, "ATOM")),
+            ..init_msg()
+        };
+        let env = mock_env("creator", &[coin(1, "BTC"), coin(5, "ATOM")]);
+        let _ = init(&mut deps, env, msg).unwrap();
+        let env = mock_env("creator", &[]);
+        let _ = handle_set_proceeds_recipient(&mut deps, env, HumanAddr::from("treasury")).unwrap();
+
+        // needs a recorded height
+        let err = query_simulate_execute(&deps, HumanAddr::from("creator"), coins(40, "ETH"), None)
+            .unwrap_err();
+        match err {
+            StdError::GenericErr { msg, .. } => {
+                assert_eq!("no height recorded, call touch first", msg.as_str())
+            }
+            e => panic!("unexpected error: {}", e),
+        }
+        let env = mock_env("anyone", &[]);
+        let _ = handle_touch(&mut deps, env).unwrap();
+
+        // rejections surface as errors, like the handler
+        let err = query_simulate_execute(&deps, HumanAddr::from("anyone"), coins(40, "ETH"), None)
+            .unwrap_err();
+        match err {
+            StdError::Unauthorized { .. } => {}
+            e => panic!("unexpected error: {}", e),
+        }
+
+        let sim = query_simulate_execute(&deps, HumanAddr::from("creator"), coins(40, "ETH"), None)
+            .unwrap();
+        assert!(query_is_active(&deps).unwrap().active);
+
+        let env = mock_env("creator", &coins(40, "ETH"));
+        let res = handle_execute(&mut deps, env, None, None, None, None).unwrap();
+        assert_eq!(sim.messages, res.messages);
+        assert_eq!(sim.log, res.log);
+        assert_eq!(sim.messages.len(), 3);
+    }
+
+    #[test]
+    fn transferable_check() {
+        let init_msg = |require_transferable_check, denom_registry: Option<&str>| InitMsg {
+            counter_offer: vec![coin(40, "ETH"), coin(10, "LOCKED")],
+            require_transferable_check,
+            denom_registry: denom_registry.map(HumanAddr::from),
+            ..init_msg()
+        };
+        let registry = |transferable: Vec<&'static str>| {
+            move |base| RegistryQuerier {
+                base,
+                registry: HumanAddr::from("registry"),
+                transferable: transferable.clone(),
+            }
+        };
+
+        // the check needs a registry to ask
+        let mut deps = mock_dependencies(20, &[]).change_querier(registry(vec!["ETH"]));
+        let env = mock_env("creator", &coins(1, "BTC"));
+        let err = init(&mut deps, env, init_msg(true, None)).unwrap_err();
+        match err {
+            StdError::GenericErr { msg, .. } => assert_eq!(
+                "require_transferable_check needs a denom_registry",
+                msg.as_str()
+            ),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // a non-transferable counter_offer denom is rejected
+        let env = mock_env("creator", &coins(1, "BTC"));
+        let err = init(&mut deps, env, init_msg(true, Some("registry"))).unwrap_err();
+        match err {
+            StdError::GenericErr { msg, .. } => {
+                assert_eq!(
+                    "counter_offer denom LOCKED is not transferable",
+                    msg.as_str()
+                )
+            }
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // not checked unless asked for
+        let env = mock_env("creator", &coins(1, "BTC"));
+        let _ = init(&mut deps, env, init_msg(false, Some("registry"))).unwrap();
+
+        // every denom approved
+        let mut deps = mock_dependencies(20, &[]).change_querier(registry(vec!["ETH", "LOCKED"]));
+        let env = mock_env("creator", &coins(1, "BTC"));
+        let _ = init(&mut deps, env, init_msg(true, Some("registry"))).unwrap();
+    }
+
+    #[test]
+    fn settle() {
+        let msg = init_msg();
+
+        // before expiry it executes, only for the owner
+        let mut deps = mock_dependencies(20, &coins(1, "BTC"));
+        let env = mock_env("creator", &coins(1, "BTC"));
+        let _ = init(&mut deps, env, msg.clone()).unwrap();
+        let env = mock_env("anyone", &coins(40, "ETH"));
+        let err = handle(&mut deps, env, HandleMsg::Settle {}).unwrap_err();
+        match err {
+            StdError::Unauthorized { .. } => {}
+            e => panic!("unexpected error: {}", e),
+        }
+        let env = mock_env("creator", &coins(40, "ETH"));
+        let res = handle(&mut deps, env, HandleMsg::Settle {}).unwrap();
+        assert_eq!(res.log[0], log("action", "execute"));
+        assert!(!query_is_active(&deps).unwrap().active);
+
+        // after expiry it burns
+        let mut deps = mock_dependencies(20, &coins(1, "BTC"));
+        let env = mock_env("creator", &coins(1, "BTC"));
+        let _ = init(&mut deps, env, msg).unwrap();
+        let mut env = mock_env("anyone", &coins(40, "ETH"));
+        env.block.height = 100_000;
+        let err = handle(&mut deps, env, HandleMsg::Settle {}).unwrap_err();
+        match err {
+            StdError::GenericErr { msg, .. } => {
+                assert_eq!("don't send funds with burn", msg.as_str())
+            }
+            e => panic!("unexpected error: {}", e),
+        }
+        let mut env = mock_env("anyone", &[]);
+        env.block.height = 100_000;
+        let res = handle(&mut deps, env, HandleMsg::Settle {}).unwrap();
+        assert_eq!(res.log[0], log("action", "burn"));
+        assert!(!query_is_active(&deps).unwrap().active);
+    }
+
+    #[test]
+    fn execute_split_collateral() {
+        let collateral = vec![coin(5, "ATOM"), coin(1_001, "BTC")];
+        let mut deps = mock_dependencies(20, &collateral);
+
+        let msg = init_msg();
+        let env = mock_env("creator", &collateral);
+        let _ = init(&mut deps, env, msg).unwrap();
+
+        // shares must add up to the whole
+        let env = mock_env("creator", &coins(40, "ETH"));
+        let recipients = vec![
+            (HumanAddr::from("hot"), 7_000),
+            (HumanAddr::from("cold"), 2_000),
+        ];
+        let err = handle_execute(&mut deps, env, None, Some(recipients), None, None).unwrap_err();
+        match err {
+            StdError::GenericErr { msg, .. } => {
+                assert_eq!("collateral_recipients must sum to 10000 bps", msg.as_str())
+            }
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // addresses must be valid
+        let env = mock_env("creator", &coins(40, "ETH"));
+        let recipients = vec![
+            (HumanAddr::from("hot"), 7_000),
+            (HumanAddr::from("x"), 3_000),
+        ];
+        let _ = handle_execute(&mut deps, env, None, Some(recipients), None, None).unwrap_err();
+
+        assert_eq!(bps_of(u128::MAX, BPS_TOTAL), u128::MAX);
+        assert_eq!(bps_of(u128::MAX, 0), 0);
+
+        // 70/30 split, rounding dust goes to the first recipient
+        let env = mock_env("creator", &coins(40, "ETH"));
+        let recipients = vec![
+            (HumanAddr::from("hot"), 7_000),
+            (HumanAddr::from("cold"), 3_000),
+        ];
+        let res = handle_execute(&mut deps, env, None, Some(recipients), None, None).unwrap();
+        assert_eq!(
+            res.messages[1..],
+            [
+                CosmosMsg::Bank(BankMsg::Send {
+                    from_address: MOCK_CONTRACT_ADDR.into(),
+                    to_address: "hot".into(),
+                    amount: vec![coin(4, "ATOM"), coin(701, "BTC")],
+                }),
+                CosmosMsg::Bank(BankMsg::Send {
+                    from_address: MOCK_CONTRACT_ADDR.into(),
+                    to_address: "cold".into(),
+                    amount: vec![coin(1, "ATOM"), coin(300, "BTC")],
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn referral() {
+        let mut deps = mock_dependencies(20, &coins(1, "BTC"));
+
+        let msg = InitMsg {
+            counter_offer: coins(1_001, "ETH"),
+            referral_bps: Some(10_000),
+            ..init_msg()
+        };
+        let env = mock_env("creator", &coins(1, "BTC"));
+        let err = init(&mut deps, env, msg.clone()).unwrap_err();
+        match err {
+            StdError::GenericErr { msg, .. } => {
+                assert_eq!("referral_bps must be below 10000", msg.as_str())
+            }
+            e => panic!("unexpected error: {}", e),
+        }
+
+        let msg = InitMsg {
+            referral_bps: Some(500),
+            ..msg
+        };
+        let env = mock_env("creator", &coins(1, "BTC"));
+        let _ = init(&mut deps, env, msg).unwrap();
+        let env = mock_env("creator", &[]);
+        let _ = handle_transfer(&mut deps, env, HumanAddr::from("owner")).unwrap();
+
+        // only the owner sets the referrer, and only once
+        let env = mock_env("creator", &[]);
+        let err = handle_set_referrer(&mut deps, env, HumanAddr::from("referrer")).unwrap_err();
+        match err {
+            StdError::Unauthorized { .. } => {}
+            e => panic!("unexpected error: {}", e),
+        }
+        for referrer in &["owner", "creator"] {
+            let env = mock_env("owner", &[]);
+            let err = handle_set_referrer(&mut deps, env, HumanAddr::from(*referrer)).unwrap_err();
+            match err {
+                StdError::GenericErr { msg, .. } => {
+                    assert_eq!("referrer cannot be the owner or creator", msg.as_str())
+                }
+                e => panic!("unexpected error: {}", e),
+            }
+        }
+        let env = mock_env("owner", &[]);
+        let _ = handle_set_referrer(&mut deps, env, HumanAddr::from("referrer")).unwrap();
+        let env = mock_env("owner", &[]);
+        let err = handle_set_referrer(&mut deps, env, HumanAddr::from("other")).unwrap_err();
+        match err {
+            StdError::GenericErr { msg, .. } => assert_eq!("referrer already set", msg.as_str()),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // creator, referrer and owner each get their part
+        let env = mock_env("owner", &coins(1_001, "ETH"));
+        let res = handle_execute(&mut deps, env, None, None, None, None).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![
+                CosmosMsg::Bank(BankMsg::Send {
+                    from_address: MOCK_CONTRACT_ADDR.into(),
+                    to_address: "creator".into(),
+                    amount: coins(951, "ETH"),
+                }),
+                CosmosMsg::Bank(BankMsg::Send {
+                    from_address: MOCK_CONTRACT_ADDR.into(),
+                    to_address: "referrer".into(),
+                    amount: coins(50, "ETH"),
+                }),
+                CosmosMsg::Bank(BankMsg::Send {
+                    from_address: MOCK_CONTRACT_ADDR.into(),
+                    to_address: "owner".into(),
+                    amount: coins(1, "BTC"),
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn expiry_warning() {
+        let mut deps = mock_dependencies(20, &[]);
+
+        let msg = init_msg();
+        let env = mock_env("creator", &coins(1, "BTC"));
+        let _ = init(&mut deps, env, msg).unwrap();
+
+        let warning_at = |deps: &mut Extern<_, _, _>, height| {
+            let mut env = mock_env("anyone", &[]);
+            env.block.height = height;
+            let _ = handle_touch(deps, env).unwrap();
+            let res = query_expiry_warning(deps, 100).unwrap();
+            (res.expiring_soon, res.blocks_remaining)
+        };
+
+        assert_eq!(warning_at(&mut deps, 99_899), (false, 101));
+        assert_eq!(warning_at(&mut deps, 99_900), (true, 100));
+        assert_eq!(warning_at(&mut deps, 99_999), (true, 1));
+        assert_eq!(warning_at(&mut deps, 100_000), (false, 0));
+        assert_eq!(warning_at(&mut deps, 200_000), (false, 0));
+    }
+
+    #[test]
+    fn coin_attr_format() {
+        let funds = vec![coin(1_000, "ATOM"), coin(5, "BTC")];
+        assert_eq!(
+            format_coins(&funds, &CoinFormat::Compact).unwrap(),
+            "1000ATOM,5BTC"
+        );
+        assert_eq!(
+            format_coins(&funds, &CoinFormat::Json).unwrap(),
+            r#"[{"denom":"ATOM","amount":"1000"},{"denom":"BTC","amount":"5"}]"#
+        );
+
+        // the configured format is used in logs
+        let mut deps = mock_dependencies(20, &[]);
+        let msg = InitMsg {
+            coin_attr_format: CoinFormat::Json,
+            ..init_msg()
+        };
+        let env = mock_env("creator", &coins(1, "BTC"));
+        let _ = init(&mut deps, env, msg).unwrap();
+        let env = mock_env("sponsor", &funds);
+        let res = handle_donate(&mut deps, env).unwrap();
+        assert_eq!(
+            res.log[2],
+            log(
+                "amount",
+                r#"[{"denom":"ATOM","amount":"1000"},{"denom":"BTC","amount":"5"}]"#
+            )
+        );
+    }
+
+    #[test]
+    fn liquidate() {
+        // 1 BTC against 40 ETH: worth 125% of the counter_offer at 50 ETH/BTC, 75% at 30 ETH/BTC
+        let msg = InitMsg {
+            oracle: Some(HumanAddr::from("oracle")),
+            maintenance_ratio_bps: Some(8_000),
+            ..init_msg()
+        };
+
+        // needs an oracle to check against
+        let mut deps = mock_dependencies(20, &[]);
+        let env = mock_env("creator", &coins(1, "BTC"));
+        let no_oracle = InitMsg {
+            oracle: None,
+            ..msg.clone()
+        };
+        let err = init(&mut deps, env, no_oracle).unwrap_err();
+        match err {
+            StdError::GenericErr { msg, .. } => {
+                assert_eq!("maintenance_ratio_bps requires an oracle", msg.as_str())
+            }
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // healthy collateral cannot be liquidated
+        let mut deps = mock_dependencies(20, &coins(1, "BTC")).change_querier(|base| {
+            with_oracle(base, vec![("BTC", "ETH", Decimal::percent(5_000))])
+        });
+        let env = mock_env("creator", &coins(1, "BTC"));
+        let _ = init(&mut deps, env, msg.clone()).unwrap();
+        let env = mock_env("creator", &[]);
+        let err = handle_liquidate(&mut deps, env).unwrap_err();
+        match err {
+            StdError::GenericErr { msg, .. } => assert_eq!(
+                "collateral at 12500 bps is not below maintenance ratio 8000 bps",
+                msg.as_str()
+            ),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // below maintenance, only the creator can close it
+        let mut deps = mock_dependencies(20, &coins(1, "BTC")).change_querier(|base| {
+            with_oracle(base, vec![("BTC", "ETH", Decimal::percent(3_000))])
+        });
+        let env = mock_env("creator", &coins(1, "BTC"));
+        let _ = init(&mut deps, env, msg.clone()).unwrap();
+        let env = mock_env("creator", &[]);
+        let _ = handle_transfer(&mut deps, env, HumanAddr::from("owner")).unwrap();
+        let env = mock_env("owner", &[]);
+        let err = handle_liquidate(&mut deps, env).unwrap_err();
+        match err {
+            StdError::Unauthorized { .. } => {}
+            e => panic!("unexpected error: {}", e),
+        }
+        let env = mock_env("creator", &[]);
+        let res = handle_liquidate(&mut deps, env).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![CosmosMsg::Bank(BankMsg::Send {
+                from_address: MOCK_CONTRACT_ADDR.into(),
+                to_address: "creator".into(),
+                amount: coins(1, "BTC"),
+            })]
+        );
+        assert_eq!(res.log[1], log("ratio_bps", "7500"));
+        assert!(!query_is_active(&deps).unwrap().active);
+
+        // an expired option is left to burn
+        let mut deps = mock_dependencies(20, &coins(1, "BTC")).change_querier(|base| {
+            with_oracle(base, vec![("BTC", "ETH", Decimal::percent(3_000))])
+        });
+        let env = mock_env("creator", &coins(1, "BTC"));
+        let _ = init(&mut deps, env, msg.clone()).unwrap();
+        let mut env = mock_env("creator", &[]);
+        env.block.height = 100_000;
+        let err = handle_liquidate(&mut deps, env).unwrap_err();
+        match err {
+            StdError::GenericErr { msg, .. } => {
+                assert_eq!("option expired, burn it instead", msg.as_str())
+            }
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // an unfunded option has no collateral to return
+        let mut deps = mock_dependencies(20, &[]).change_querier(|base| {
+            with_oracle(base, vec![("BTC", "ETH", Decimal::percent(3_000))])
+        });
+        let deferred = InitMsg {
+            allow_deferred_funding: true,
+            ..msg
+        };
+        let env = mock_env("creator", &[]);
+        let _ = init(&mut deps, env, deferred).unwrap();
+        assert!(!query_risk_params(&deps).unwrap().liquidatable_now);
+        let env = mock_env("creator", &[]);
+        let err = handle_liquidate(&mut deps, env).unwrap_err();
+        match err {
+            StdError::GenericErr { msg, .. } => {
+                assert_eq!("option awaiting collateral", msg.as_str())
+            }
+            e => panic!("unexpected error: {}", e),
+        }
+    }
+
+    #[test]
+    fn risk_params() {
+        let msg = InitMsg {
+            oracle: Some(HumanAddr::from("oracle")),
+            maintenance_ratio_bps: Some(8_000),
+            ..init_msg()
+        };
+
+        let mut deps = mock_dependencies(20, &[]).change_querier(|base| {
+            with_oracle(base, vec![("BTC", "ETH", Decimal::percent(5_000))])
+        });
+        let env = mock_env("creator", &coins(1, "BTC"));
+        let _ = init(&mut deps, env, msg.clone()).unwrap();
+        let res = query_risk_params(&deps).unwrap();
+        assert_eq!(
+            res,
+            RiskParamsResponse {
+                maintenance_ratio_bps: Some(8_000),
+                oracle: Some(HumanAddr::from("oracle")),
+                liquidatable_now: false,
+                current_ratio_bps: Some(12_500),
+            }
+        );
+
+        let mut deps = mock_dependencies(20, &[]).change_querier(|base| {
+            with_oracle(base, vec![("BTC", "ETH", Decimal::percent(3_000))])
+        });
+        let env = mock_env("creator", &coins(1, "BTC"));
+        let _ = init(&mut deps, env, msg).unwrap();
+        let res = query_risk_params(&deps).unwrap();
+        assert!(res.liquidatable_now);
+        assert_eq!(res.current_ratio_bps, Some(7_500));
+    }
+
+    #[test]
+    fn schema_version() {
+        let deps = mock_dependencies(20, &[]);
+
+        // answered even before init
+        let res: SchemaVersionResponse =
+            from_binary(&query(&deps, QueryMsg::SchemaVersion {}).unwrap()).unwrap();
+        assert_eq!(res.schema_version, 1);
+        assert_eq!(res.contract, "simple-option");
+        assert_eq!(res.version, env!("CARGO_PKG_VERSION"));
+    }
+
+    #[test]
+    fn operational_state() {
+        let mut deps = mock_dependencies(20, &coins(1, "BTC"));
+
+        // only touch works without an option
+        let res = query_operational_state(&deps).unwrap();
+        assert!(!res.paused);
+        assert_eq!(res.admin, None);
+        assert_eq!(res.handlers_enabled, vec!["touch".to_string()]);
+
+        let msg = InitMsg {
+            admin: Some(HumanAddr::from("admin")),
+            ..init_msg()
+        };
+        let env = mock_env("creator", &coins(1, "BTC"));
+        let _ = init(&mut deps, env, msg).unwrap();
+        let res: OperationalStateResponse =
+            from_binary(&query(&deps, QueryMsg::OperationalState {}).unwrap()).unwrap();
+        assert!(!res.paused);
+        assert_eq!(res.admin, Some(HumanAddr::from("admin")));
+        assert_eq!(res.handlers_enabled.len(), HANDLERS.len());
+
+        // frozen, every owner change and payout is off
+        let env = mock_env("admin", &[]);
+        let _ = handle(&mut deps, env, HandleMsg::FreezeOption {}).unwrap();
+        let res = query_operational_state(&deps).unwrap();
+        assert!(res.paused);
+        assert_eq!(
+            res.handlers_enabled.len(),
+            HANDLERS.len() - FROZEN_HANDLERS.len()
+        );
+        for handler in &["transfer", "execute", "burn", "reissue", "liquidate"] {
+            assert!(!res.handlers_enabled.contains(&handler.to_string()));
+        }
+        for handler in &["unfreeze_option", "set_metadata", "donate", "touch"] {
+            assert!(res.handlers_enabled.contains(&handler.to_string()));
+        }
+        // and really blocked
+        let env = mock_env("creator", &[]);
+        let _ = handle_transfer(&mut deps, env, HumanAddr::from("owner")).unwrap_err();
+
+        let env = mock_env("admin", &[]);
+        let _ = handle(&mut deps, env, HandleMsg::UnfreezeOption {}).unwrap();
+        let res = query_operational_state(&deps).unwrap();
+        assert!(!res.paused);
+        assert_eq!(res.handlers_enabled.len(), HANDLERS.len());
+    }
+
+    #[test]
+    fn contract_info() {
+        let mut deps = mock_dependencies(20, &coins(1, "BTC"));
+
+        // answered even before init
+        let res: ContractInfoResponse =
+            from_binary(&query(&deps, QueryMsg::ContractInfo {}).unwrap()).unwrap();
+        assert_eq!(res.contract, "simple-option");
+        assert_eq!(res.version, env!("CARGO_PKG_VERSION"));
+        assert_eq!(res.admin, None);
+        assert!(!res.paused);
+        assert_eq!(res.total_options, 0);
+
+        let msg = InitMsg {
+            admin: Some(HumanAddr::from("admin")),
+            ..init_msg()
+        };
+        let env = mock_env("creator", &coins(1, "BTC"));
+        let _ = init(&mut deps, env, msg).unwrap();
+        let res = query_contract_info(&deps).unwrap();
+        assert_eq!(res.admin, Some(HumanAddr::from("admin")));
+        assert!(!res.paused);
+        assert_eq!(res.total_options, 1);
+
+        // paused follows frozen
+        let env = mock_env("admin", &[]);
+        let _ = handle(&mut deps, env, HandleMsg::FreezeOption {}).unwrap();
+        assert!(query_contract_info(&deps).unwrap().paused);
+        let env = mock_env("admin", &[]);
+        let _ = handle(&mut deps, env, HandleMsg::UnfreezeOption {}).unwrap();
+        assert!(!query_contract_info(&deps).unwrap().paused);
+
+        // executing removes the only option, the deployment keeps its admin
+        let env = mock_env("creator", &coins(40, "ETH"));
+        let _ = handle_execute(&mut deps, env, None, None, None, None).unwrap();
+        let res = query_contract_info(&deps).unwrap();
+        assert_eq!(res.admin, Some(HumanAddr::from("admin")));
+        assert!(!res.paused);
+        assert_eq!(res.total_options, 0);
+    }
+
+    #[test]
+    fn expires_at_u64_max() {
+        let mut deps = mock_dependencies(20, &coins(1, "BTC"));
+
+        let msg = InitMsg {
+            expires: u64::MAX,
+            min_lifetime: Some(u64::MAX),
+            ..init_msg()
+        };
+        // the lifetime check saturates instead of overflowing
+        let env = mock_env("creator", &coins(1, "BTC"));
+        let _ = init(&mut deps, env, msg).unwrap();
+
+        // the last possible block is still before expiry
+        let mut env = mock_env("anyone", &[]);
+        env.block.height = u64::MAX - 1;
+        let _ = handle_touch(&mut deps, env).unwrap();
+        let res = query_expiry_warning(&deps, u64::MAX).unwrap();
+        assert!(res.expiring_soon);
+        assert_eq!(res.blocks_remaining, 1);
+        assert!(
+            query_can_execute(&deps, HumanAddr::from("creator"), coins(40, "ETH"))
+                .unwrap()
+                .can
+        );
+        let _ = query_lifecycle(&deps).unwrap();
+        let _ = query_nft_info(&deps).unwrap();
+
+        let mut env = mock_env("anyone", &[]);
+        env.block.height = u64::MAX - 1;
+        let _ = handle_burn(&mut deps, env).unwrap_err();
+        let mut env = mock_env("creator", &[]);
+        env.block.height = u64::MAX - 1;
+        let _ = handle_propose_shorten(&mut deps, env, u64::MAX - 1).unwrap_err();
+
+        let mut env = mock_env("creator", &coins(40, "ETH"));
+        env.block.height = u64::MAX - 1;
+        let _ = handle_execute(&mut deps, env, None, None, None, None).unwrap();
+    }
+
+    #[test]
+    fn role() {
+        let mut deps = mock_dependencies(20, &[]);
+
+        let msg = init_msg();
+        let env = mock_env("creator", &coins(1, "BTC"));
+        let _ = init(&mut deps, env, msg).unwrap();
+
+        // creator wins while they also own it
+        let role = |deps: &Extern<_, _, _>, address: &str| {
+            query_role(deps, HumanAddr::from(address)).unwrap().role
+        };
+        assert_eq!(role(&deps, "creator"), Role::Creator);
+
+        let env = mock_env("creator", &[]);
+        let _ = handle_transfer(&mut deps, env, HumanAddr::from("owner")).unwrap();
+        let env = mock_env("owner", &[]);
+        let _ = handle_set_beneficiary(&mut deps, env, Some(HumanAddr::from("heir"))).unwrap();
+
+        assert_eq!(role(&deps, "creator"), Role::Creator);
+        assert_eq!(role(&deps, "owner"), Role::Owner);
+        assert_eq!(role(&deps, "heir"), Role::Beneficiary);
+        assert_eq!(role(&deps, "anyone"), Role::None);
+    }
+
+    #[test]
+    fn deferred_funding() {
+        let mut deps = mock_dependencies(20, &coins(1, "BTC"));
+
+        let msg = init_msg();
+
+        // collateral is required unless deferred
+        let env = mock_env("creator", &[]);
+        let err = init(&mut deps, env, msg.clone()).unwrap_err();
+        match err {
+            StdError::GenericErr { msg, .. } => assert_eq!("must send collateral", msg.as_str()),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        let msg = InitMsg {
+            allow_deferred_funding: true,
+            ..msg
+        };
+        let env = mock_env("creator", &[]);
+        let _ = init(&mut deps, env, msg).unwrap();
+        assert!(query_config(&deps).unwrap().pending_funding);
+
+        // cannot exercise while unfunded
+        let env = mock_env("creator", &coins(40, "ETH"));
+        let err = handle_execute(&mut deps, env, None, None, None, None).unwrap_err();
+        match err {
+            StdError::GenericErr { msg, .. } => {
+                assert_eq!("option awaiting collateral", msg.as_str())
+            }
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // only the creator funds it
+        let env = mock_env("anyone", &coins(1, "BTC"));
+        let err = handle_fund_collateral(&mut deps, env).unwrap_err();
+        match err {
+            StdError::Unauthorized { .. } => {}
+            e => panic!("unexpected error: {}", e),
+        }
+        let env = mock_env("creator", &coins(1, "BTC"));
+        let res = handle_fund_collateral(&mut deps, env).unwrap();
+        assert_eq!(res.log[1], log("amount", "1BTC"));
+        let state = query_config(&deps).unwrap();
+        assert!(!state.pending_funding);
+        assert_eq!(state.collateral, coins(1, "BTC"));
+
+        // once is enough
+        let env = mock_env("creator", &coins(1, "BTC"));
+        let err = handle_fund_collateral(&mut deps, env).unwrap_err();
+        match err {
+            StdError::GenericErr { msg, .. } => assert_eq!("option already funded", msg.as_str()),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // and now it can be exercised
+        let env = mock_env("creator", &coins(40, "ETH"));
+        let _ = handle_execute(&mut deps, env, None, None, None, None).unwrap();
+    }
+
+    #[test]
+    fn can_transfer() {
+        let mut deps = mock_dependencies(20, &[]);
+
+        let msg = init_msg();
+        let env = mock_env("creator", &coins(1, "BTC"));
+        let _ = init(&mut deps, env, msg).unwrap();
+
+        let can = |sender: &str, recipient: &str| {
+            query_can_transfer(&deps, HumanAddr::from(sender), HumanAddr::from(recipient)).unwrap()
+        };
+
+        let res = can("creator", "someone");
+        assert!(res.can);
+        assert_eq!(res.reason, None);
+
+        let res = can("anyone", "someone");
+        assert!(!res.can);
+        assert_eq!(res.reason_code, Some(ReasonCode::NotOwner));
+
+        let res = can("creator", "creator");
+        assert!(!res.can);
+        assert_eq!(res.reason_code, Some(ReasonCode::InvalidRecipient));
+        assert_eq!(
+            res.reason,
+            Some("cannot transfer to current owner".to_string())
+        );
+
+        let res = can("creator", MOCK_CONTRACT_ADDR);
+        assert_eq!(res.reason_code, Some(ReasonCode::InvalidRecipient));
+
+        // an address transfer would fail to canonicalize
+        let res = can("creator", "x");
+        assert!(!res.can);
+        assert_eq!(res.reason_code, Some(ReasonCode::InvalidRecipient));
+    }
+
+    #[test]
+    fn ratio_rounding_to_zero() {
+        let mut deps = mock_dependencies(20, &coins(3, "BTC")).change_querier(|base| {
+            with_oracle(
+                base,
+                vec![
+                    ("BTC", "ETH", Decimal::one()),
+                    ("ETH", "USD", Decimal::one()),
+                ],
+            )
+        });
+
+        // 3 BTC at 1 ETH/BTC * 1/4 rounds down to 0 ETH
+        let msg = InitMsg {
+            counter_offer: vec![],
+            counter_offer_ratio: Some(("ETH".to_string(), 1, 4)),
+            settlement_denoms: Some(vec!["USD".to_string()]),
+            oracle: Some(HumanAddr::from("oracle")),
+            ..init_msg()
+        };
+        let env = mock_env("creator", &coins(3, "BTC"));
+        let _ = init(&mut deps, env, msg).unwrap();
+        assert_eq!(
+            query_effective_counter_offer(&deps).unwrap().counter_offer,
+            coins(0, "ETH")
+        );
+
+        // neither nothing nor a token settlement payment exercises it
+        for funds in &[vec![], coins(0, "ETH"), coins(1, "USD")] {
+            let env = mock_env("creator", funds);
+            let err = handle_execute(&mut deps, env, None, None, None, None).unwrap_err();
+            match err {
+                StdError::GenericErr { msg, .. } => {
+                    assert_eq!("counter offer rounds to zero", msg.as_str())
+                }
+                e => panic!("unexpected error: {}", e),
+            }
+        }
+
+        // nor is a free exercise previewed
+        let env = mock_env("anyone", &[]);
+        let _ = handle_touch(&mut deps, env).unwrap();
+        match query_exercise_preview(&deps).unwrap_err() {
+            StdError::GenericErr { msg, .. } => {
+                assert_eq!("counter offer rounds to zero", msg.as_str())
+            }
+            e => panic!("unexpected error: {}", e),
+        }
+    }
+
+    #[test]
+    fn burn_bounty() {
+        let collateral = vec![coin(100, "ATOM"), coin(1, "BTC")];
+        let msg = init_msg();
+
+        // without a bounty the creator gets everything
+        let mut deps = mock_dependencies(20, &collateral);
+        let env = mock_env("creator", &collateral);
+        let _ = init(&mut deps, env, msg.clone()).unwrap();
+        let mut env = mock_env("keeper", &[]);
+        env.block.height = 100_000;
+        let res = handle_burn(&mut deps, env).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![CosmosMsg::Bank(BankMsg::Send {
+                from_address: MOCK_CONTRACT_ADDR.into(),
+                to_address: "creator".into(),
+                amount: collateral.clone(),
+            })]
+        );
+
+        let mut deps = mock_dependencies(20, &collateral);
+        let env = mock_env("creator", &collateral);
+        let _ = init(&mut deps, env, msg).unwrap();
+
+        // only the creator sets it, within the collateral
+        let env = mock_env("anyone", &[]);
+        let err = handle_set_auto_burn_bounty(&mut deps, env, Some(coins(1, "ATOM"))).unwrap_err();
+        match err {
+            StdError::Unauthorized { .. } => {}
+            e => panic!("unexpected error: {}", e),
+        }
+        let env = mock_env("creator", &[]);
+        let err = handle_set_auto_burn_bounty(&mut deps, env, Some(coins(1, "ETH"))).unwrap_err();
+        match err {
+            StdError::GenericErr { msg, .. } => {
+                assert_eq!("burn bounty exceeds collateral", msg.as_str())
+            }
+            e => panic!("unexpected error: {}", e),
+        }
+        let env = mock_env("creator", &[]);
+        let _ = handle_set_auto_burn_bounty(&mut deps, env, Some(coins(5, "ATOM"))).unwrap();
+
+        // the burner collects it
+        let mut env = mock_env("keeper", &[]);
+        env.block.height = 100_000;
+        let res = handle_burn(&mut deps, env).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![
+                CosmosMsg::Bank(BankMsg::Send {
+                    from_address: MOCK_CONTRACT_ADDR.into(),
+                    to_address: "creator".into(),
+                    amount: vec![coin(95, "ATOM"), coin(1, "BTC")],
+                }),
+                CosmosMsg::Bank(BankMsg::Send {
+                    from_address: MOCK_CONTRACT_ADDR.into(),
+                    to_address: "keeper".into(),
+                    amount: coins(5, "ATOM"),
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn flat() {
+        let mut deps = mock_dependencies(20, &[]);
+
+        let msg = InitMsg {
+            counter_offer: vec![coin(40, "ETH"), coin(10, "USD")],
+            coin_attr_format: CoinFormat::Json,
+            ..init_msg()
+        };
+        let env = mock_env("creator", &[coin(1, "BTC"), coin(7, "ATOM")]);
+        let _ = init(&mut deps, env, msg).unwrap();
+        let env = mock_env("creator", &[]);
+        let _ = handle_transfer(&mut deps, env, HumanAddr::from("owner")).unwrap();
+        let env = mock_env("anyone", &[]);
+        let _ = handle_touch(&mut deps, env).unwrap();
+
+        let res = query_flat(&deps).unwrap();
+        assert_eq!(
+            res.columns,
+            vec![
+                "id",
+                "creator",
+                "owner",
+                "status",
+                "expires",
+                "collateral_str",
+                "counter_offer_str",
+                "created_height"
+            ]
+        );
+        // always compact, whatever the log format
+        assert_eq!(
+            res.values,
+            vec![
+                MOCK_CONTRACT_ADDR,
+                "creator",
+                "owner",
+                "active",
+                "100000",
+                "7ATOM,1BTC",
+                "40ETH,10USD",
+                "12345"
+            ]
+        );
+    }
+
+    #[test]
+    fn exercise_preview() {
+        let collateral = vec![coin(5, "ATOM"), coin(1, "BTC")];
+        let mut deps = mock_dependencies(20, &collateral);
+
+        let msg = InitMsg {
+            counter_offer: coins(1_000, "ETH"),
+            dust_threshold: Some(coins(10, "ATOM")),
+            referral_bps: Some(250),
+            ..init_msg()
+        };
+        let env = mock_env("creator", &collateral);
+        let _ = init(&mut deps, env, msg).unwrap();
+        let env = mock_env("creator", &[]);
+        let _ = handle_transfer(&mut deps, env, HumanAddr::from("owner")).unwrap();
+        let env = mock_env("anyone", &[]);
+        let _ = handle_touch(&mut deps, env).unwrap();
+
+        // no referrer yet, the creator gets the whole payment plus the dust
+        let res = query_exercise_preview(&deps).unwrap();
+        assert_eq!(res.required_payment, coins(1_000, "ETH"));
+        assert_eq!(res.owner_receives, coins(1, "BTC"));
+        assert_eq!(
+            res.creator_receives,
+            vec![coin(5, "ATOM"), coin(1_000, "ETH")]
+        );
+        assert_eq!(res.fee_collector_receives, vec![]);
+
+        let env = mock_env("owner", &[]);
+        let _ = handle_set_referrer(&mut deps, env, HumanAddr::from("referrer")).unwrap();
+        let res = query_exercise_preview(&deps).unwrap();
+        assert_eq!(
+            res.creator_receives,
+            vec![coin(5, "ATOM"), coin(975, "ETH")]
+        );
+        assert_eq!(res.fee_collector_receives, coins(25, "ETH"));
+
+        // matches what execute sends
+        let env = mock_env("owner", &res.required_payment);
+        let exec = handle_execute(&mut deps, env, None, None, None, None).unwrap();
+        let send = |to: &str, amount: Vec<Coin>| {
+            CosmosMsg::Bank(BankMsg::Send {
+                from_address: MOCK_CONTRACT_ADDR.into(),
+                to_address: to.into(),
+                amount,
+            })
+        };
+        assert_eq!(
+            exec.messages,
+            vec![
+                send("creator", coins(975, "ETH")),
+                send("referrer", res.fee_collector_receives),
+                send("owner", res.owner_receives),
+                send("creator", coins(5, "ATOM")),
+            ]
+        );
+    }
+
+    #[test]
+    fn sanctions() {
+        let msg = InitMsg {
+            sanctions: Some(HumanAddr::from("sanctions")),
+            ..init_msg()
+        };
+        let with_list = |listed: Vec<&'static str>| {
+            move |base| SanctionsQuerier {
+                base,
+                sanctions: HumanAddr::from("sanctions"),
+                listed: listed.clone(),
+            }
+        };
+
+        // a listed counterparty holds the option
+        let mut deps =
+            mock_dependencies(20, &coins(1, "BTC")).change_querier(with_list(vec!["creator"]));
+        let env = mock_env("creator", &coins(1, "BTC"));
+        let _ = init(&mut deps, env, msg.clone()).unwrap();
+        let env = mock_env("creator", &[]);
+        let _ = handle_transfer(&mut deps, env, HumanAddr::from("owner")).unwrap();
+        let env = mock_env("owner", &coins(40, "ETH"));
+        let err = handle_execute(&mut deps, env, None, None, None, None).unwrap_err();
+        match err {
+            StdError::GenericErr { msg, .. } => {
+                assert_eq!("cannot settle to sanctioned address creator", msg.as_str())
+            }
+            e => panic!("unexpected error: {}", e),
+        }
+        assert!(query_is_active(&deps).unwrap().active);
+
+        // cleared counterparties settle
+        let mut deps =
+            mock_dependencies(20, &coins(1, "BTC")).change_querier(with_list(vec!["someone"]));
+        let env = mock_env("creator", &coins(1, "BTC"));
+        let _ = init(&mut deps, env, msg).unwrap();
+        let env = mock_env("creator", &[]);
         let _ = handle_transfer(&mut deps, env, HumanAddr::from("owner")).unwrap();
+        let env = mock_env("owner", &coins(40, "ETH"));
+        let _ = handle_execute(&mut deps, env, None, None, None, None).unwrap();
+    }
+
+    #[test]
+    fn owner_pnl() {
+        let mut deps = mock_dependencies(20, &[]);
+
+        // a call on 2 BTC for 20_000 USD
+        let msg = InitMsg {
+            counter_offer: coins(20_000, "USD"),
+            ..init_msg()
+        };
+        let env = mock_env("creator", &coins(2, "BTC"));
+        let _ = init(&mut deps, env, msg).unwrap();
+
+        let pnl =
+            |spot: u128, quote: &str| query_owner_pnl(&deps, Uint128(spot), quote.to_string());
+
+        // in the money
+        let res = pnl(12_500, "USD").unwrap();
+        assert!(res.positive);
+        assert_eq!(res.amount, Uint128(5_000));
+
+        // out of the money
+        let res = pnl(9_000, "USD").unwrap();
+        assert!(!res.positive);
+        assert_eq!(res.amount, Uint128(2_000));
+
+        // at the money
+        let res = pnl(10_000, "USD").unwrap();
+        assert!(res.positive);
+        assert_eq!(res.amount, Uint128::zero());
+
+        let err = pnl(10_000, "EUR").unwrap_err();
+        match err {
+            StdError::GenericErr { msg, .. } => assert_eq!("neither leg is in EUR", msg.as_str()),
+            e => panic!("unexpected error: {}", e),
+        }
+    }
 
-        // random cannot execute
+    #[test]
+    fn owner_pnl_put() {
+        let mut deps = mock_dependencies(20, &[]);
+
+        // a put selling 2 BTC for 20_000 USD
+        let msg = InitMsg {
+            counter_offer: coins(20_000, "USD"),
+            option_type: OptionType::Put,
+            underlying: Some(coins(2, "BTC")),
+            ..init_msg()
+        };
+        let env = mock_env("creator", &coins(20_000, "USD"));
+        let _ = init(&mut deps, env, msg).unwrap();
+
+        let res = query_owner_pnl(&deps, Uint128(8_000), "USD".to_string()).unwrap();
+        assert!(res.positive);
+        assert_eq!(res.amount, Uint128(4_000));
+        let res = query_owner_pnl(&deps, Uint128(11_000), "USD".to_string()).unwrap();
+        assert!(!res.positive);
+        assert_eq!(res.amount, Uint128(2_000));
+    }
+
+    #[test]
+    fn migrate_v1_state() {
+        let mut deps = mock_dependencies(20, &[]);
+
+        let old = StateV1 {
+            creator: HumanAddr::from("creator"),
+            owner: HumanAddr::from("owner"),
+            collateral: coins(1, "BTC"),
+            counter_offer: coins(40, "ETH"),
+            expires: 100_000,
+        };
+        singleton(&mut deps.storage, CONFIG_KEY).save(&old).unwrap();
+
+        // the old layout does not load as the current State
+        let _ = query_config(&deps).unwrap_err();
+
+        let env = mock_env("anyone", &[]);
+        let _ = migrate(&mut deps, env, MigrateMsg {}).unwrap();
+        let state = query_config(&deps).unwrap();
+        assert_eq!(state.owner, HumanAddr::from("owner"));
+        assert_eq!(state.collateral, coins(1, "BTC"));
+        assert_eq!(state.counter_offer, coins(40, "ETH"));
+        assert_eq!(state.expires, 100_000);
+        assert_eq!(state.option_type, OptionType::Call);
+        assert_eq!(state.admin, None);
+        assert_eq!(state.created_height, 0);
+        assert_eq!(state.referral_bps, 0);
+        assert!(!state.pending_funding);
+        assert_eq!(
+            state_version_read(&deps.storage).load().unwrap(),
+            STATE_VERSION
+        );
+
+        // the contract address and admin are recorded too, as init did not store them yet
+        assert_eq!(query_solvency(&deps).unwrap().shortfall, coins(1, "BTC"));
+        assert_eq!(admin_read(&deps.storage).load().unwrap(), None);
+
+        // running it again is a no-op
+        let env = mock_env("anyone", &[]);
+        let _ = migrate(&mut deps, env, MigrateMsg {}).unwrap();
+        assert_eq!(query_config(&deps).unwrap(), state);
+    }
+
+    #[test]
+    fn freeze_option() {
+        let mut deps = mock_dependencies(20, &coins(1, "BTC"));
+
+        let counter_offer = coins(40, "ETH");
+        let collateral = coins(1, "BTC");
+        let msg = InitMsg {
+            counter_offer: counter_offer.clone(),
+            admin: Some(HumanAddr::from("admin")),
+            ..init_msg()
+        };
+        let env = mock_env("creator", &collateral);
+        let _ = init(&mut deps, env, msg).unwrap();
+
+        // only the admin can freeze
+        for sender in &["creator", "anyone"] {
+            let env = mock_env(*sender, &[]);
+            let err = handle(&mut deps, env, HandleMsg::FreezeOption {}).unwrap_err();
+            match err {
+                StdError::Unauthorized { .. } => {}
+                e => panic!("unexpected error: {}", e),
+            }
+        }
+        let env = mock_env("admin", &[]);
+        let res = handle(&mut deps, env, HandleMsg::FreezeOption {}).unwrap();
+        assert_eq!(res.log[0], log("action", "freeze"));
+        assert!(query_config(&deps).unwrap().frozen);
+
+        // transfer, execute and burn are all blocked
+        let env = mock_env("creator", &[]);
+        let err = handle_transfer(&mut deps, env, HumanAddr::from("owner")).unwrap_err();
+        match err {
+            StdError::GenericErr { msg, .. } => assert_eq!("option is frozen", msg.as_str()),
+            e => panic!("unexpected error: {}", e),
+        }
         let env = mock_env("creator", &counter_offer);
-        let err = handle_execute(&mut deps, env).unwrap_err();
+        let err = handle_execute(&mut deps, env, None, None, None, None).unwrap_err();
         match err {
-            StdError::Unauthorized { .. } => {}
+            StdError::GenericErr { msg, .. } => assert_eq!("option is frozen", msg.as_str()),
             e => panic!("unexpected error: {}", e),
         }
-
-        // expired cannot execute
-        let mut env = mock_env("owner", &counter_offer);
+        let mut env = mock_env("anyone", &[]);
         env.block.height = 200_000;
-        let err = handle_execute(&mut deps, env).unwrap_err();
+        let err = handle_burn(&mut deps, env).unwrap_err();
         match err {
-            StdError::GenericErr { msg, .. } => assert_eq!("option expired", msg.as_str()),
+            StdError::GenericErr { msg, .. } => assert_eq!("option is frozen", msg.as_str()),
             e => panic!("unexpected error: {}", e),
         }
+        let mut env = mock_env("anyone", &[]);
+        env.block.height = 200_000;
+        let _ = handle_touch(&mut deps, env).unwrap();
+        let res = query_can_burn(&deps, vec![]).unwrap();
+        assert_eq!(res.reason_code, Some(ReasonCode::Frozen));
 
-        // bad counter_offer cannot execute
-        let env = mock_env("owner", &coins(39, "ETH"));
-        let err = handle_execute(&mut deps, env).unwrap_err();
+        // unfreezing restores them
+        let env = mock_env("anyone", &[]);
+        let _ = handle(&mut deps, env, HandleMsg::UnfreezeOption {}).unwrap_err();
+        let env = mock_env("admin", &[]);
+        let res = handle(&mut deps, env, HandleMsg::UnfreezeOption {}).unwrap();
+        assert_eq!(res.log[0], log("action", "unfreeze"));
+        let env = mock_env("creator", &[]);
+        let _ = handle_transfer(&mut deps, env, HumanAddr::from("owner")).unwrap();
+        let env = mock_env("owner", &counter_offer);
+        let res = handle_execute(&mut deps, env, None, None, None, None).unwrap();
+        assert_eq!(res.messages.len(), 2);
+    }
+
+    #[test]
+    fn burn_recipient() {
+        let mut deps = mock_dependencies(20, &coins(1, "BTC"));
+
+        let collateral = coins(1, "BTC");
+        let msg = init_msg();
+        let env = mock_env("creator", &collateral);
+        let _ = init(&mut deps, env, msg).unwrap();
+
+        let env = mock_env("creator", &[]);
+        let _ = handle_transfer(&mut deps, env, HumanAddr::from("owner")).unwrap();
+
+        // only the creator can set it
+        let env = mock_env("owner", &[]);
+        let err = handle_set_burn_recipient(&mut deps, env, Some("owner".into())).unwrap_err();
         match err {
-            StdError::GenericErr { msg, .. } => assert!(msg.contains("counter offer")),
+            StdError::Unauthorized { .. } => {}
             e => panic!("unexpected error: {}", e),
         }
 
-        // proper execution
-        let env = mock_env("owner", &counter_offer);
-        let res = handle_execute(&mut deps, env).unwrap();
-        assert_eq!(res.messages.len(), 2);
+        // must be a valid address
+        let env = mock_env("creator", &[]);
+        let _ = handle_set_burn_recipient(&mut deps, env, Some("x".into())).unwrap_err();
+
+        let env = mock_env("creator", &[]);
+        let res = handle_set_burn_recipient(&mut deps, env, Some("vault".into())).unwrap();
+        assert_eq!(res.log[1], log("recipient", "vault"));
+
+        // burn sends the collateral to the vault
+        let mut env = mock_env("anyone", &[]);
+        env.block.height = 200_000;
+        let res = handle_burn(&mut deps, env).unwrap();
         assert_eq!(
             res.messages[0],
             CosmosMsg::Bank(BankMsg::Send {
                 from_address: MOCK_CONTRACT_ADDR.into(),
-                to_address: "creator".into(),
-                amount: counter_offer,
+                to_address: "vault".into(),
+                amount: collateral.clone(),
             })
         );
+
+        // once cleared, it goes back to the creator
+        let env = mock_env("creator", &collateral);
+        let msg = init_msg();
+        let _ = init(&mut deps, env, msg).unwrap();
+        let env = mock_env("creator", &[]);
+        let _ = handle_set_burn_recipient(&mut deps, env, Some("vault".into())).unwrap();
+        let env = mock_env("creator", &[]);
+        let _ = handle_set_burn_recipient(&mut deps, env, None).unwrap();
+        let mut env = mock_env("anyone", &[]);
+        env.block.height = 200_000;
+        let res = handle_burn(&mut deps, env).unwrap();
         assert_eq!(
-            res.messages[1],
+            res.messages[0],
             CosmosMsg::Bank(BankMsg::Send {
                 from_address: MOCK_CONTRACT_ADDR.into(),
-                to_address: "owner".into(),
+                to_address: "creator".into(),
                 amount: collateral,
             })
         );
-
-        // check deleted
-        let _ = query_config(&deps).unwrap_err();
     }
 
     #[test]
-    fn burn() {
+    fn parties() {
         let mut deps = mock_dependencies(20, &[]);
 
+        let msg = InitMsg {
+            admin: Some(HumanAddr::from("admin")),
+            ..init_msg()
+        };
+        let env = mock_env("creator", &coins(1, "BTC"));
+        let _ = init(&mut deps, env, msg).unwrap();
+
+        let env = mock_env("creator", &[]);
+        let _ = handle_transfer(&mut deps, env, HumanAddr::from("owner")).unwrap();
+        let env = mock_env("owner", &[]);
+        let _ = handle_set_beneficiary(&mut deps, env, Some(HumanAddr::from("heir"))).unwrap();
+        let env = mock_env("creator", &[]);
+        let _ = handle_set_proceeds_recipient(&mut deps, env, "treasury".into()).unwrap();
+        let env = mock_env("creator", &[]);
+        let callback = (HumanAddr::from("staking"), Binary::from(br#"{"stake":{}}"#));
+        let _ = handle_set_proceeds_callback(&mut deps, env, Some(callback)).unwrap();
+
+        let res = query_parties(&deps).unwrap();
+        assert_eq!(
+            res,
+            PartiesResponse {
+                creator: HumanAddr::from("creator"),
+                owner: HumanAddr::from("owner"),
+                admin: Some(HumanAddr::from("admin")),
+                beneficiary: Some(HumanAddr::from("heir")),
+                pending_owner: None,
+                proceeds_recipient: Some(HumanAddr::from("treasury")),
+                proceeds_callback: Some(HumanAddr::from("staking")),
+                burn_recipient: None,
+                referrer: None,
+            }
+        );
+    }
+
+    #[test]
+    fn proceeds_callback() {
+        let mut deps = mock_dependencies(20, &coins(1, "BTC"));
+
         let counter_offer = coins(40, "ETH");
-        let collateral = coins(1, "BTC");
         let msg = InitMsg {
             counter_offer: counter_offer.clone(),
-            expires: 100_000,
+            ..init_msg()
         };
-        let env = mock_env("creator", &collateral);
-
-        // we can just call .unwrap() to assert this was a success
+        let env = mock_env("creator", &coins(1, "BTC"));
         let _ = init(&mut deps, env, msg).unwrap();
 
-        // set new owner
         let env = mock_env("creator", &[]);
         let _ = handle_transfer(&mut deps, env, HumanAddr::from("owner")).unwrap();
 
-        // non-expired cannot execute
-        let env = mock_env("anyone", &[]);
-        let err = handle_burn(&mut deps, env).unwrap_err();
+        // only the creator can set it, to a valid address
+        let callback = (HumanAddr::from("staking"), Binary::from(br#"{"stake":{}}"#));
+        let env = mock_env("owner", &[]);
+        let err = handle_set_proceeds_callback(&mut deps, env, Some(callback.clone())).unwrap_err();
         match err {
-            StdError::GenericErr { msg, .. } => assert_eq!("option not yet expired", msg.as_str()),
+            StdError::Unauthorized { .. } => {}
             e => panic!("unexpected error: {}", e),
         }
+        let env = mock_env("creator", &[]);
+        let invalid = (HumanAddr::from("x"), callback.1.clone());
+        let _ = handle_set_proceeds_callback(&mut deps, env, Some(invalid)).unwrap_err();
 
-        // with funds cannot execute
-        let mut env = mock_env("anyone", &counter_offer);
-        env.block.height = 200_000;
-        let err = handle_burn(&mut deps, env).unwrap_err();
-        match err {
-            StdError::GenericErr { msg, .. } => {
-                assert_eq!("don't send funds with burn", msg.as_str())
+        // takes precedence over a proceeds recipient
+        let env = mock_env("creator", &[]);
+        let _ = handle_set_proceeds_recipient(&mut deps, env, "treasury".into()).unwrap();
+        let env = mock_env("creator", &[]);
+        let _ = handle_set_proceeds_callback(&mut deps, env, Some(callback)).unwrap();
+
+        let env = mock_env("owner", &counter_offer);
+        let res = handle_execute(&mut deps, env, None, None, None, None).unwrap();
+        assert_eq!(res.messages.len(), 2);
+        assert_eq!(
+            res.messages[0],
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "staking".into(),
+                msg: Binary::from(br#"{"stake":{}}"#),
+                send: counter_offer,
+            })
+        );
+        assert_eq!(
+            res.messages[1],
+            CosmosMsg::Bank(BankMsg::Send {
+                from_address: MOCK_CONTRACT_ADDR.into(),
+                to_address: "owner".into(),
+                amount: coins(1, "BTC"),
+            })
+        );
+    }
+
+    #[test]
+    fn execute_pays_current_owner() {
+        let mut deps = mock_dependencies(20, &coins(1, "BTC"));
+
+        let counter_offer = coins(40, "ETH");
+        let collateral = coins(1, "BTC");
+        let msg = InitMsg {
+            counter_offer: counter_offer.clone(),
+            ..init_msg()
+        };
+        let env = mock_env("creator", &collateral);
+        let _ = init(&mut deps, env, msg).unwrap();
+
+        // passed on twice, once by offer and accept
+        let env = mock_env("creator", &[]);
+        let _ = handle_transfer(&mut deps, env, HumanAddr::from("owner")).unwrap();
+        let env = mock_env("owner", &[]);
+        let _ = handle_offer_transfer(&mut deps, env, HumanAddr::from("buyer")).unwrap();
+        let env = mock_env("buyer", &[]);
+        let _ = handle_accept_ownership(&mut deps, env).unwrap();
+
+        // earlier owners cannot exercise
+        for sender in &["creator", "owner"] {
+            let env = mock_env(*sender, &counter_offer);
+            let err = handle_execute(&mut deps, env, None, None, None, None).unwrap_err();
+            match err {
+                StdError::Unauthorized { .. } => {}
+                e => panic!("unexpected error: {}", e),
             }
-            e => panic!("unexpected error: {}", e),
         }
 
-        // expired returns funds
-        let mut env = mock_env("anyone", &[]);
-        env.block.height = 200_000;
-        let res = handle_burn(&mut deps, env).unwrap();
-        assert_eq!(res.messages.len(), 1);
+        // the collateral goes to the owner at execute time
+        let env = mock_env("buyer", &counter_offer);
+        let res = handle_execute(&mut deps, env, None, None, None, None).unwrap();
+        assert_eq!(
+            res.messages[1],
+            CosmosMsg::Bank(BankMsg::Send {
+                from_address: MOCK_CONTRACT_ADDR.into(),
+                to_address: "buyer".into(),
+                amount: collateral,
+            })
+        );
+    }
+
+    #[test]
+    fn health_check() {
+        let mut deps = mock_dependencies(20, &[]);
+
+        let msg = init_msg();
+        let env = mock_env("creator", &coins(1, "BTC"));
+        let _ = init(&mut deps, env, msg).unwrap();
+
+        let res = query_health_check(&deps).unwrap();
+        assert!(res.healthy);
+        assert_eq!(res.violations, Vec::<String>::new());
+
+        // as if written by a buggy migration
+        let mut state = query_config(&deps).unwrap();
+        state.owner = HumanAddr::from("x");
+        state.collateral = vec![];
+        state.counter_offer = vec![];
+        state.expires = state.created_height - 1;
+        state.referral_bps = BPS_TOTAL + 1;
+        config(&mut deps.storage).save(&state).unwrap();
+
+        let res = query_health_check(&deps).unwrap();
+        assert!(!res.healthy);
+        assert_eq!(
+            res.violations,
+            vec![
+                "owner is not a valid address",
+                "collateral is empty",
+                "counter_offer is empty",
+                "expires is before created_height",
+                "referral_bps is not below 10000",
+            ]
+        );
+    }
+
+    #[test]
+    fn referrer_cleared_with_owner() {
+        let mut deps = mock_dependencies(20, &coins(1, "BTC"));
+
+        let msg = InitMsg {
+            referral_bps: Some(5_000),
+            ..init_msg()
+        };
+        let env = mock_env("creator", &coins(1, "BTC"));
+        let _ = init(&mut deps, env, msg).unwrap();
+        let referrer = |deps: &Extern<_, _, _>| query_config(deps).unwrap().referrer;
+
+        // by transfer
+        let env = mock_env("creator", &[]);
+        let _ = handle_transfer(&mut deps, env, HumanAddr::from("owner")).unwrap();
+        let env = mock_env("owner", &[]);
+        let _ = handle_set_referrer(&mut deps, env, HumanAddr::from("friend")).unwrap();
+        let env = mock_env("owner", &[]);
+        let _ = handle_transfer(&mut deps, env, HumanAddr::from("buyer")).unwrap();
+        assert_eq!(referrer(&deps), None);
+
+        // by offer and accept
+        let env = mock_env("buyer", &[]);
+        let _ = handle_set_referrer(&mut deps, env, HumanAddr::from("friend")).unwrap();
+        let env = mock_env("buyer", &[]);
+        let _ = handle_offer_transfer(&mut deps, env, HumanAddr::from("second")).unwrap();
+        let env = mock_env("second", &[]);
+        let _ = handle_accept_ownership(&mut deps, env).unwrap();
+        assert_eq!(referrer(&deps), None);
+
+        // by claiming as beneficiary
+        let env = mock_env("second", &[]);
+        let _ = handle_set_referrer(&mut deps, env, HumanAddr::from("friend")).unwrap();
+        let env = mock_env("second", &[]);
+        let _ = handle_set_beneficiary(&mut deps, env, Some(HumanAddr::from("heir"))).unwrap();
+        let mut env = mock_env("heir", &[]);
+        env.block.height = 100_000;
+        let _ = handle_claim_as_beneficiary(&mut deps, env).unwrap();
+        assert_eq!(referrer(&deps), None);
+
+        // and by reissue
+        let env = mock_env("heir", &[]);
+        let _ = handle_set_referrer(&mut deps, env, HumanAddr::from("friend")).unwrap();
+        let mut env = mock_env("creator", &[]);
+        env.block.height = 100_000;
+        let _ = handle_reissue(&mut deps, env, 200_000, coins(40, "ETH")).unwrap();
+        assert_eq!(referrer(&deps), None);
+
+        // the new owner's payment goes in full to the creator
+        let mut env = mock_env("creator", &coins(40, "ETH"));
+        env.block.height = 100_000;
+        let res = handle_execute(&mut deps, env, None, None, None, None).unwrap();
         assert_eq!(
             res.messages[0],
             CosmosMsg::Bank(BankMsg::Send {
                 from_address: MOCK_CONTRACT_ADDR.into(),
                 to_address: "creator".into(),
-                amount: collateral,
+                amount: coins(40, "ETH"),
             })
         );
+    }
 
-        // check deleted
-        let _ = query_config(&deps).unwrap_err();
+    #[test]
+    fn freeze_blocks_every_owner_change_and_payout() {
+        let mut deps = mock_dependencies(20, &coins(1, "BTC"));
+
+        let msg = InitMsg {
+            admin: Some(HumanAddr::from("admin")),
+            ..init_msg()
+        };
+        let env = mock_env("creator", &coins(1, "BTC"));
+        let _ = init(&mut deps, env, msg).unwrap();
+
+        let env = mock_env("creator", &[]);
+        let _ = handle_transfer(&mut deps, env, HumanAddr::from("owner")).unwrap();
+        let env = mock_env("owner", &[]);
+        let _ = handle_set_beneficiary(&mut deps, env, Some(HumanAddr::from("heir"))).unwrap();
+        let env = mock_env("owner", &[]);
+        let _ = handle_offer_transfer(&mut deps, env, HumanAddr::from("buyer")).unwrap();
+
+        let env = mock_env("admin", &[]);
+        let _ = handle(&mut deps, env, HandleMsg::FreezeOption {}).unwrap();
+        assert!(!query_is_active(&deps).unwrap().active);
+        let state = query_config(&deps).unwrap();
+        assert_eq!(status(&state, Some(50_000)), "frozen");
+
+        let assert_frozen = |res: StdResult<HandleResponse>| match res.unwrap_err() {
+            StdError::GenericErr { msg, .. } => assert_eq!("option is frozen", msg.as_str()),
+            e => panic!("unexpected error: {}", e),
+        };
+        let env = mock_env("owner", &[]);
+        assert_frozen(handle_offer_transfer(
+            &mut deps,
+            env,
+            HumanAddr::from("other"),
+        ));
+        let env = mock_env("buyer", &[]);
+        assert_frozen(handle_accept_ownership(&mut deps, env));
+        let mut env = mock_env("heir", &[]);
+        env.block.height = 100_000;
+        assert_frozen(handle_claim_as_beneficiary(&mut deps, env));
+        let mut env = mock_env("creator", &[]);
+        env.block.height = 100_000;
+        assert_frozen(handle_reissue(&mut deps, env, 200_000, coins(40, "ETH")));
+        let env = mock_env("creator", &[]);
+        assert_frozen(handle_liquidate(&mut deps, env));
+        assert_eq!(query_config(&deps).unwrap(), state);
+
+        // unfrozen, the pending offer can be accepted again
+        let env = mock_env("admin", &[]);
+        let _ = handle(&mut deps, env, HandleMsg::UnfreezeOption {}).unwrap();
+        assert!(query_is_active(&deps).unwrap().active);
+        let env = mock_env("buyer", &[]);
+        let _ = handle_accept_ownership(&mut deps, env).unwrap();
+    }
+
+    #[test]
+    fn handlers_keep_health_check_invariants() {
+        let mut deps = mock_dependencies(20, &[]);
+
+        // an empty counter_offer is rejected at init and reissue
+        let msg = InitMsg {
+            counter_offer: vec![coin(0, "ETH")],
+            admin: Some(HumanAddr::from("admin")),
+            ..init_msg()
+        };
+        let env = mock_env("creator", &coins(1, "BTC"));
+        let err = init(&mut deps, env, msg.clone()).unwrap_err();
+        match err {
+            StdError::GenericErr { msg, .. } => {
+                assert_eq!(
+                    "must set counter_offer or counter_offer_ratio",
+                    msg.as_str()
+                )
+            }
+            e => panic!("unexpected error: {}", e),
+        }
+        let msg = InitMsg {
+            counter_offer: coins(40, "ETH"),
+            ..msg
+        };
+        let env = mock_env("creator", &coins(1, "BTC"));
+        let _ = init(&mut deps, env, msg).unwrap();
+        let env = mock_env("creator", &[]);
+        let err = handle_reissue(&mut deps, env, 200_000, vec![]).unwrap_err();
+        match err {
+            StdError::GenericErr { msg, .. } => assert_eq!("must set counter_offer", msg.as_str()),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // so is a transfer to an invalid address
+        let env = mock_env("creator", &[]);
+        let _ = handle_transfer(&mut deps, env, HumanAddr::from("x")).unwrap_err();
+        let env = mock_env("creator", &[]);
+        let _ = handle_offer_transfer(&mut deps, env, HumanAddr::from("x")).unwrap_err();
+
+        // donating and force expiring in the creation block stay healthy
+        let env = mock_env("sponsor", &coins(5, "ATOM"));
+        let _ = handle_donate(&mut deps, env).unwrap();
+        let env = mock_env("admin", &[]);
+        let _ = handle_force_expire(&mut deps, env).unwrap();
+        let res = query_health_check(&deps).unwrap();
+        assert_eq!(res.violations, Vec::<String>::new());
+        assert!(res.healthy);
     }
 }
@@ -1,5 +1,5 @@
-use crate::state::State;
-use cosmwasm_std::{Coin, HumanAddr};
+use crate::state::{CoinFormat, OptionType, State};
+use cosmwasm_std::{Binary, Coin, CosmosMsg, Decimal, HumanAddr, LogAttribute, Uint128};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
@@ -7,26 +7,432 @@ use serde::{Deserialize, Serialize};
 pub struct InitMsg {
     // owner and creator come from env
     // collateral comes from env
+    /// Must be non-empty unless counter_offer_ratio is set. For a put this is what the owner
+    /// receives on exercise, and the creator must send exactly it at init
     pub counter_offer: Vec<Coin>,
+    /// (denom, numerator, denominator): require the collateral's value in denom, as priced by
+    /// the oracle, times numerator / denominator on exercise, rejected if that rounds to zero.
+    /// Needs an oracle. Mutually exclusive with counter_offer.
+    pub counter_offer_ratio: Option<(String, u64, u64)>,
     pub expires: u64,
+    /// Defaults to a call
+    #[serde(default)]
+    pub option_type: OptionType,
+    /// Put only, and required for it: what the owner delivers on exercise
+    pub underlying: Option<Vec<Coin>>,
+    /// Minimum blocks between creation and expiry, defaults to 1
+    pub min_lifetime: Option<u64>,
+    /// Released collateral below its denom's threshold is swept to the creator on execute
+    pub dust_threshold: Option<Vec<Coin>>,
+    /// Denoms the owner may pay in instead, if worth the counter_offer per the oracle
+    pub settlement_denoms: Option<Vec<String>>,
+    /// Optional admin allowed to force-expire the option in emergencies
+    pub admin: Option<HumanAddr>,
+    /// Optional price oracle implementing oracle::OracleQueryMsg
+    pub oracle: Option<HumanAddr>,
+    /// Optional label for explorers, at most 128 bytes
+    pub label: Option<String>,
+    /// If set, every counter_offer denom must be reported transferable by denom_registry
+    #[serde(default)]
+    pub require_transferable_check: bool,
+    /// Contract implementing registry::RegistryQueryMsg, only queried at init
+    pub denom_registry: Option<HumanAddr>,
+    /// Share of the counter_offer paid to the owner's referrer on execute, defaults to 0.
+    /// Must be below BPS_TOTAL, so the creator's leg is never empty
+    pub referral_bps: Option<u16>,
+    /// How coins are rendered in log attributes, defaults to compact
+    #[serde(default)]
+    pub coin_attr_format: CoinFormat,
+    /// Creator may liquidate once the collateral is worth less than this share
+    /// (in bps) of the counter_offer, per the oracle. Requires oracle
+    pub maintenance_ratio_bps: Option<u64>,
+    /// If set, init may be sent without collateral, to be posted later with FundCollateral
+    #[serde(default)]
+    pub allow_deferred_funding: bool,
+    /// Optional sanctions list implementing sanctions::SanctionsQueryMsg.
+    /// Execute is rejected if it would pay a listed address
+    pub sanctions: Option<HumanAddr>,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MigrateMsg {}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum HandleMsg {
-    /// Owner can transfer to a new owner
+    /// Owner can transfer to a new owner. Rejected if funds are attached
     Transfer { recipient: HumanAddr },
-    /// Owner can post counter_offer on unexpired option to execute and get the collateral
-    Execute {},
+    /// Owner can post counter_offer on unexpired option to execute and get the collateral.
+    /// If expected_counter_offer is set, it must match the live counter_offer.
+    /// If collateral_recipients is set, the collateral is split between them by bps
+    /// (summing to 10000), with rounding dust going to the first.
+    /// If max_price is set, it is rejected once the required payment, as quoted by the oracle
+    /// for a ratio or settlement denom, exceeds it. If deadline is set, it is rejected after
+    /// that height.
+    Execute {
+        expected_counter_offer: Option<Vec<Coin>>,
+        collateral_recipients: Option<Vec<(HumanAddr, u16)>>,
+        max_price: Option<Uint128>,
+        deadline: Option<u64>,
+    },
     /// Burn will release collateral if expired
     Burn {},
+    /// Admin can force the option to expire immediately, so it can only be burned
+    ForceExpire {},
+    /// Anyone can record the current block height for height-dependent queries
+    Touch {},
+    /// Creator can set labels for UIs, replacing any previous metadata
+    SetMetadata {
+        name: Option<String>,
+        description: Option<String>,
+    },
+    /// Owner can name (or clear) a beneficiary to inherit the option on expiry
+    SetBeneficiary { beneficiary: Option<HumanAddr> },
+    /// Beneficiary can take ownership once the option expired
+    ClaimAsBeneficiary {},
+    /// Owner can offer ownership, which only moves once the recipient accepts
+    OfferTransfer { recipient: HumanAddr },
+    /// Pending owner can accept an offered transfer
+    AcceptOwnership {},
+    /// Owner can withdraw an offered transfer
+    CancelTransfer {},
+    /// Creator can roll an expired option (or one they hold) into new terms
+    /// on the same collateral, becoming its owner again
+    Reissue {
+        new_expires: u64,
+        new_counter_offer: Vec<Coin>,
+    },
+    /// Creator can redirect the counter_offer paid on execute
+    SetProceedsRecipient { recipient: HumanAddr },
+    /// Anyone can add funds to the collateral as a gift, once funded and unless it sets a
+    /// counter_offer_ratio
+    Donate {},
+    /// Creator can propose an earlier expiry on an unexpired option
+    ProposeShorten { new_expires: u64 },
+    /// Owner can accept the proposed earlier expiry
+    AcceptShorten {},
+    /// Executes before expiry and burns after it, under the same rules as each
+    Settle {},
+    /// Owner can name, once while they own it, who referred them to receive the referral cut.
+    /// Neither the owner nor the creator can be the referrer
+    SetReferrer { referrer: HumanAddr },
+    /// Creator can close an under-collateralized option and take back the collateral
+    Liquidate {},
+    /// Creator can post the collateral of an option created without it
+    FundCollateral {},
+    /// Creator can set (or clear) a bounty, taken from the collateral, for whoever burns the option
+    SetAutoBurnBounty { bounty: Option<Vec<Coin>> },
+    /// Admin can block every owner change and payout until unfrozen
+    FreezeOption {},
+    /// Admin can lift a freeze
+    UnfreezeOption {},
+    /// Creator can route the collateral released on burn elsewhere, such as a vault, or clear it
+    SetBurnRecipient { recipient: Option<HumanAddr> },
+    /// Creator can have the counter_offer paid on execute sent to a contract with their
+    /// message, as a WasmMsg::Execute, or clear it
+    SetProceedsCallback {
+        callback: Option<(HumanAddr, Binary)>,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
     Config {},
+    /// Returns true while the option exists, has not been executed or burned,
+    /// is funded and is not frozen
+    IsActive {},
+    /// Returns the value of the collateral in quote_denom, priced by the oracle
+    CollateralValue {
+        quote_denom: String,
+    },
+    /// Returns the block height last recorded by Touch
+    LastHeight {},
+    /// Checks if sender could execute with funds, at the height last recorded by Touch
+    CanExecute {
+        sender: HumanAddr,
+        funds: Vec<Coin>,
+    },
+    /// Checks if a burn with funds would succeed, at the height last recorded by Touch
+    CanBurn {
+        funds: Vec<Coin>,
+    },
+    /// Returns whether this is a call or a put
+    OptionType {},
+    /// Compares the contract balance to the collateral it owes,
+    /// the shortfall is how much of each denom to top up
+    Solvency {},
+    /// Returns the heights an option moves through, for timelines
+    Lifecycle {},
+    /// Returns the price per unit of collateral, in quote_denom, at which executing breaks even.
+    /// Only defined for collateral in a single denom.
+    Breakeven {
+        quote_denom: String,
+    },
+    /// Returns the recipient of an offered transfer, if any
+    PendingOwner {},
+    /// Returns the denoms of both legs
+    Denoms {},
+    /// Returns the counter_offer currently required to execute, resolving any ratio
+    EffectiveCounterOffer {},
+    /// Returns the option as NFT-style metadata for generic explorers
+    NftInfo {},
+    /// Returns the messages and logs an execute by sender with funds would produce,
+    /// at the height last recorded by Touch
+    SimulateExecute {
+        sender: HumanAddr,
+        funds: Vec<Coin>,
+        collateral_recipients: Option<Vec<(HumanAddr, u16)>>,
+    },
+    /// Warns when the option is unexpired but within threshold_blocks of expiry,
+    /// at the height last recorded by Touch
+    ExpiryWarning {
+        threshold_blocks: u64,
+    },
+    /// Returns the liquidation parameters and the live collateral ratio per the oracle
+    RiskParams {},
+    /// Returns the version of the query responses, with the contract version
+    SchemaVersion {},
+    /// Classifies address as one of the option's parties
+    Role {
+        address: HumanAddr,
+    },
+    /// Checks if sender could transfer, or offer, the option to recipient
+    CanTransfer {
+        sender: HumanAddr,
+        recipient: HumanAddr,
+    },
+    /// Returns the option's scalar fields as strings, in a fixed column order for CSV export
+    Flat {},
+    /// Returns what each party would receive if the owner exercised now with the required
+    /// payment, at the height last recorded by Touch. Fails wherever execute would
+    ExercisePreview {},
+    /// Returns what the owner would gain by exercising with the underlying at spot_price
+    /// units of quote_denom. Needs a single denom on each leg, one of them quote_denom
+    OwnerPnl {
+        spot_price: Uint128,
+        quote_denom: String,
+    },
+    /// Returns every address holding a role on the option, for access audits
+    Parties {},
+    /// Checks the stored State against the invariants init and the handlers maintain
+    HealthCheck {},
+    /// Returns an overview of the deployment for explorers, answered even without an option
+    ContractInfo {},
+    /// Returns the pause state, the admin and which handlers the pause currently allows
+    OperationalState {},
 }
 
 // We define a custom struct for each query response
 pub type ConfigResponse = State;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct IsActiveResponse {
+    pub active: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CollateralValueResponse {
+    pub quote_denom: String,
+    pub value: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LastHeightResponse {
+    /// None if Touch was never called
+    pub height: Option<u64>,
+}
+
+/// Machine-readable reason an action would be rejected
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ReasonCode {
+    NotOwner,
+    Expired,
+    NotExpired,
+    WrongFunds,
+    UnexpectedFunds,
+    NotFunded,
+    InvalidRecipient,
+    ZeroCounterOffer,
+    Frozen,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CanResponse {
+    pub can: bool,
+    /// Human readable reason, if rejected
+    pub reason: Option<String>,
+    pub reason_code: Option<ReasonCode>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct OptionTypeResponse {
+    pub option_type: OptionType,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SolvencyResponse {
+    pub solvent: bool,
+    /// Per denom amount the balance is short of the obligations
+    pub shortfall: Vec<Coin>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LifecycleResponse {
+    pub created_height: u64,
+    /// First height execute is allowed, None as an option is exercisable from creation
+    pub exercise_from: Option<u64>,
+    pub expires: u64,
+    /// End of a grace period after expiry, None as options have none
+    pub grace_until: Option<u64>,
+    /// Height last recorded by Touch, if any
+    pub now: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BreakevenResponse {
+    pub quote_denom: String,
+    pub price: Decimal,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingOwnerResponse {
+    pub pending_owner: Option<HumanAddr>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DenomsResponse {
+    pub collateral_denoms: Vec<String>,
+    pub counter_offer_denoms: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct EffectiveCounterOfferResponse {
+    pub counter_offer: Vec<Coin>,
+}
+
+/// NFT-style metadata, status is as of the height last recorded by Touch
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct NftInfoResponse {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub attributes: Vec<Trait>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Trait {
+    pub trait_type: String,
+    pub value: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SimulateExecuteResponse {
+    pub messages: Vec<CosmosMsg>,
+    pub log: Vec<LogAttribute>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ExpiryWarningResponse {
+    pub expiring_soon: bool,
+    pub blocks_remaining: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RiskParamsResponse {
+    pub maintenance_ratio_bps: Option<u64>,
+    pub oracle: Option<HumanAddr>,
+    pub liquidatable_now: bool,
+    /// Collateral value as bps of the counter_offer value, None without an oracle
+    pub current_ratio_bps: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SchemaVersionResponse {
+    /// Bumped on every breaking change to a query response
+    pub schema_version: u32,
+    pub contract: String,
+    pub version: String,
+}
+
+/// An address's relation to the option. If it has several, the first listed wins
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    Creator,
+    Owner,
+    Beneficiary,
+    None,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RoleResponse {
+    pub role: Role,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FlatResponse {
+    pub columns: Vec<String>,
+    /// One per column, in the same order
+    pub values: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ExercisePreviewResponse {
+    pub owner_receives: Vec<Coin>,
+    /// The creator's share of the payment, paid to the proceeds recipient if set, plus swept dust
+    pub creator_receives: Vec<Coin>,
+    /// The referrer's cut, if a referrer is set
+    pub fee_collector_receives: Vec<Coin>,
+    pub required_payment: Vec<Coin>,
+}
+
+/// A signed amount in quote_denom, zero counts as positive
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct OwnerPnlResponse {
+    pub positive: bool,
+    pub amount: Uint128,
+}
+
+/// Optional roles are None while unset
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PartiesResponse {
+    pub creator: HumanAddr,
+    pub owner: HumanAddr,
+    pub admin: Option<HumanAddr>,
+    pub beneficiary: Option<HumanAddr>,
+    pub pending_owner: Option<HumanAddr>,
+    pub proceeds_recipient: Option<HumanAddr>,
+    /// Contract the counter_offer is sent to with the creator's callback message
+    pub proceeds_callback: Option<HumanAddr>,
+    pub burn_recipient: Option<HumanAddr>,
+    pub referrer: Option<HumanAddr>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct HealthCheckResponse {
+    pub healthy: bool,
+    /// One human readable line per broken invariant
+    pub violations: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct OperationalStateResponse {
+    pub paused: bool,
+    pub admin: Option<HumanAddr>,
+    /// HandleMsg names, in snake_case, not blocked by the pause. Each still has its own
+    /// sender and funds checks. Only touch once the option is gone
+    pub handlers_enabled: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ContractInfoResponse {
+    pub contract: String,
+    pub version: String,
+    /// The admin set at init, kept after the option is gone
+    pub admin: Option<HumanAddr>,
+    /// Whether the option is frozen, false once it is gone
+    pub paused: bool,
+    /// A contract holds a single option, so 1 until it is executed or burned, then 0
+    pub total_options: u64,
+}
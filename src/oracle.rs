@@ -0,0 +1,42 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_std::{
+    to_binary, Decimal, HumanAddr, Querier, QueryRequest, StdError, StdResult, WasmQuery,
+};
+
+/// The query interface we expect the configured price oracle to implement
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum OracleQueryMsg {
+    /// Price of one unit of base, expressed in units of quote
+    Price { base: String, quote: String },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PriceResponse {
+    pub price: Decimal,
+}
+
+/// Returns the price of base in quote, without querying if they are the same denom
+pub fn query_price<Q: Querier>(
+    querier: &Q,
+    oracle: &HumanAddr,
+    base: &str,
+    quote: &str,
+) -> StdResult<Decimal> {
+    if base == quote {
+        return Ok(Decimal::one());
+    }
+    let request = QueryRequest::Wasm(WasmQuery::Smart {
+        contract_addr: oracle.clone(),
+        msg: to_binary(&OracleQueryMsg::Price {
+            base: base.to_string(),
+            quote: quote.to_string(),
+        })?,
+    });
+    let res: PriceResponse = querier
+        .query(&request)
+        .map_err(|_| StdError::generic_err(format!("no price for {} in {}", base, quote)))?;
+    Ok(res.price)
+}
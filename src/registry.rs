@@ -0,0 +1,35 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_std::{to_binary, HumanAddr, Querier, QueryRequest, StdError, StdResult, WasmQuery};
+
+/// The query interface we expect the configured denom registry to implement
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RegistryQueryMsg {
+    /// Whether denom can be sent with a bank transfer
+    Transferable { denom: String },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TransferableResponse {
+    pub transferable: bool,
+}
+
+/// Asks the registry whether denom is transferable
+pub fn query_transferable<Q: Querier>(
+    querier: &Q,
+    registry: &HumanAddr,
+    denom: &str,
+) -> StdResult<bool> {
+    let request = QueryRequest::Wasm(WasmQuery::Smart {
+        contract_addr: registry.clone(),
+        msg: to_binary(&RegistryQueryMsg::Transferable {
+            denom: denom.to_string(),
+        })?,
+    });
+    let res: TransferableResponse = querier.query(&request).map_err(|_| {
+        StdError::generic_err(format!("denom registry has no answer for {}", denom))
+    })?;
+    Ok(res.transferable)
+}
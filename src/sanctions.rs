@@ -0,0 +1,35 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_std::{to_binary, HumanAddr, Querier, QueryRequest, StdError, StdResult, WasmQuery};
+
+/// The query interface we expect the configured sanctions list to implement
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SanctionsQueryMsg {
+    /// Whether address must not be paid
+    IsSanctioned { address: HumanAddr },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SanctionedResponse {
+    pub sanctioned: bool,
+}
+
+/// Asks the sanctions list whether address is listed, failing closed if it cannot answer
+pub fn query_sanctioned<Q: Querier>(
+    querier: &Q,
+    sanctions: &HumanAddr,
+    address: &HumanAddr,
+) -> StdResult<bool> {
+    let request = QueryRequest::Wasm(WasmQuery::Smart {
+        contract_addr: sanctions.clone(),
+        msg: to_binary(&SanctionsQueryMsg::IsSanctioned {
+            address: address.clone(),
+        })?,
+    });
+    let res: SanctionedResponse = querier.query(&request).map_err(|_| {
+        StdError::generic_err(format!("sanctions list has no answer for {}", address))
+    })?;
+    Ok(res.sanctioned)
+}
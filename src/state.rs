@@ -1,13 +1,134 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::{Coin, HumanAddr, Storage};
+use cosmwasm_std::{Binary, Coin, HumanAddr, Storage};
 use cosmwasm_storage::{singleton, singleton_read, ReadonlySingleton, Singleton};
 
+use crate::contract::DEFAULT_MIN_LIFETIME;
+
 pub static CONFIG_KEY: &[u8] = b"config";
+pub static LAST_HEIGHT_KEY: &[u8] = b"last_height";
+pub static CONTRACT_ADDR_KEY: &[u8] = b"contract_addr";
+pub static ADMIN_KEY: &[u8] = b"admin";
+pub static STATE_VERSION_KEY: &[u8] = b"state_version";
+
+/// Layout of the State stored under CONFIG_KEY, bumped whenever a migration is needed.
+/// Contracts without a stored version hold a StateV1
+pub const STATE_VERSION: u32 = 2;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct State {
+    pub creator: HumanAddr,
+    pub owner: HumanAddr,
+    /// Optional admin allowed to intervene in emergencies
+    pub admin: Option<HumanAddr>,
+    /// Optional price oracle used to value the collateral
+    pub oracle: Option<HumanAddr>,
+    pub collateral: Vec<Coin>,
+    pub counter_offer: Vec<Coin>,
+    /// If set, the counter_offer is computed from the collateral at exercise time
+    pub counter_offer_ratio: Option<(String, u64, u64)>,
+    pub expires: u64,
+    pub option_type: OptionType,
+    pub metadata: Metadata,
+    /// Set by the owner, may claim ownership once expired
+    pub beneficiary: Option<HumanAddr>,
+    /// Collateral below its denom's threshold goes to the creator on execute
+    pub dust_threshold: Option<Vec<Coin>>,
+    /// Denoms the counter_offer may also be paid in, converted by the oracle
+    pub settlement_denoms: Option<Vec<String>>,
+    pub created_height: u64,
+    /// Minimum blocks between creation and expiry set at init, also applied on reissue
+    pub min_lifetime: u64,
+    /// Offered ownership by the owner, becomes owner on accepting
+    pub pending_owner: Option<HumanAddr>,
+    /// Set by the creator, receives the counter_offer instead of them
+    pub proceeds_recipient: Option<HumanAddr>,
+    /// Fixed at init for explorers, bounded by MAX_LABEL_LEN bytes
+    pub label: Option<String>,
+    /// Earlier expiry proposed by the creator, applied once the owner accepts
+    pub proposed_expires: Option<u64>,
+    /// Set once by the owner, receives referral_bps of the payment on execute
+    pub referrer: Option<HumanAddr>,
+    pub referral_bps: u16,
+    pub coin_attr_format: CoinFormat,
+    /// Creator may liquidate once the collateral is worth less than this share of the counter_offer
+    pub maintenance_ratio_bps: Option<u64>,
+    /// Created without collateral, exercise is rejected until the creator funds it
+    pub pending_funding: bool,
+    /// Set by the creator, paid out of the collateral to whoever burns the option
+    pub burn_bounty: Option<Vec<Coin>>,
+    /// Contract implementing sanctions::SanctionsQueryMsg, checked for every execute payee
+    pub sanctions: Option<HumanAddr>,
+    /// Set by the admin, blocks every owner change and payout
+    pub frozen: bool,
+    /// Set by the creator, receives the collateral released on burn instead of them
+    pub burn_recipient: Option<HumanAddr>,
+    /// Set by the creator, receives the counter_offer on execute along with this message,
+    /// taking precedence over proceeds_recipient
+    pub proceeds_callback: Option<(HumanAddr, Binary)>,
+}
+
+/// Which party posts which leg. State always stores the leg the contract holds as collateral
+/// and the leg the owner pays as counter_offer, so execute settles both types the same way.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum OptionType {
+    /// Creator posts the underlying as collateral, owner may buy it for the counter_offer
+    Call,
+    /// Inverted: the creator posts InitMsg.counter_offer (the strike), the owner may deliver
+    /// InitMsg.underlying for it. Init stores them as collateral and counter_offer respectively
+    Put,
+}
+
+/// How coins are rendered in emitted log attributes
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CoinFormat {
+    /// "1000ATOM,5BTC"
+    Compact,
+    /// [{"denom":"ATOM","amount":"1000"},{"denom":"BTC","amount":"5"}]
+    Json,
+}
+
+// by hand, as deriving Default for enums needs a newer toolchain than CI pins
+#[allow(clippy::complexity)]
+impl Default for OptionType {
+    fn default() -> Self {
+        OptionType::Call
+    }
+}
+
+#[allow(clippy::complexity)]
+impl Default for CoinFormat {
+    fn default() -> Self {
+        CoinFormat::Compact
+    }
+}
+
+impl OptionType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OptionType::Call => "call",
+            OptionType::Put => "put",
+        }
+    }
+}
+
+pub const MAX_NAME_LEN: usize = 64;
+pub const MAX_DESCRIPTION_LEN: usize = 512;
+pub const MAX_LABEL_LEN: usize = 128;
+
+/// Human-friendly labels for UIs, bounded by MAX_NAME_LEN and MAX_DESCRIPTION_LEN bytes
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct Metadata {
+    pub name: Option<String>,
+    pub description: Option<String>,
+}
+
+/// The original State layout, which migrate upgrades in place
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StateV1 {
     pub creator: HumanAddr,
     pub owner: HumanAddr,
     pub collateral: Vec<Coin>,
@@ -15,10 +136,86 @@ pub struct State {
     pub expires: u64,
 }
 
-pub fn config<S: Storage>(storage: &mut S) -> Singleton<S, State> {
+impl From<StateV1> for State {
+    /// Fills every later field with its init default, created_height 0 as it is unknown
+    fn from(old: StateV1) -> Self {
+        State {
+            creator: old.creator,
+            owner: old.owner,
+            admin: None,
+            oracle: None,
+            collateral: old.collateral,
+            counter_offer: old.counter_offer,
+            counter_offer_ratio: None,
+            expires: old.expires,
+            option_type: OptionType::default(),
+            metadata: Metadata::default(),
+            beneficiary: None,
+            dust_threshold: None,
+            settlement_denoms: None,
+            created_height: 0,
+            min_lifetime: DEFAULT_MIN_LIFETIME,
+            pending_owner: None,
+            proceeds_recipient: None,
+            label: None,
+            proposed_expires: None,
+            referrer: None,
+            referral_bps: 0,
+            coin_attr_format: CoinFormat::default(),
+            maintenance_ratio_bps: None,
+            pending_funding: false,
+            burn_bounty: None,
+            sanctions: None,
+            frozen: false,
+            burn_recipient: None,
+            proceeds_callback: None,
+        }
+    }
+}
+
+pub fn config<S: Storage>(storage: &mut S) -> Singleton<'_, S, State> {
     singleton(storage, CONFIG_KEY)
 }
 
-pub fn config_read<S: Storage>(storage: &S) -> ReadonlySingleton<S, State> {
+pub fn config_read<S: Storage>(storage: &S) -> ReadonlySingleton<'_, S, State> {
     singleton_read(storage, CONFIG_KEY)
 }
+
+/// Most recent block height recorded by HandleMsg::Touch, as queries lack Env
+pub fn last_height<S: Storage>(storage: &mut S) -> Singleton<'_, S, u64> {
+    singleton(storage, LAST_HEIGHT_KEY)
+}
+
+pub fn last_height_read<S: Storage>(storage: &S) -> ReadonlySingleton<'_, S, u64> {
+    singleton_read(storage, LAST_HEIGHT_KEY)
+}
+
+/// Address of this contract, recorded at init as queries lack Env
+pub fn contract_addr<S: Storage>(storage: &mut S) -> Singleton<'_, S, HumanAddr> {
+    singleton(storage, CONTRACT_ADDR_KEY)
+}
+
+pub fn contract_addr_read<S: Storage>(storage: &S) -> ReadonlySingleton<'_, S, HumanAddr> {
+    singleton_read(storage, CONTRACT_ADDR_KEY)
+}
+
+/// Admin set at init, kept for ContractInfo after the option is executed or burned
+pub fn admin<S: Storage>(storage: &mut S) -> Singleton<'_, S, Option<HumanAddr>> {
+    singleton(storage, ADMIN_KEY)
+}
+
+pub fn admin_read<S: Storage>(storage: &S) -> ReadonlySingleton<'_, S, Option<HumanAddr>> {
+    singleton_read(storage, ADMIN_KEY)
+}
+
+pub fn config_v1_read<S: Storage>(storage: &S) -> ReadonlySingleton<'_, S, StateV1> {
+    singleton_read(storage, CONFIG_KEY)
+}
+
+pub fn state_version<S: Storage>(storage: &mut S) -> Singleton<'_, S, u32> {
+    singleton(storage, STATE_VERSION_KEY)
+}
+
+pub fn state_version_read<S: Storage>(storage: &S) -> ReadonlySingleton<'_, S, u32> {
+    singleton_read(storage, STATE_VERSION_KEY)
+}